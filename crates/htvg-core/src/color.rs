@@ -0,0 +1,626 @@
+//! CSS Color 4 parsing and color space conversion.
+//!
+//! Named colors, hex and the `rgb()`, `hsl()`, `hwb()`, `lab()`, `lch()`,
+//! `oklab()`, `oklch()` and `color()` functions in both the legacy comma
+//! syntax and the space/slash syntax. Colors outside sRGB are gamut mapped
+//! by reducing OKLCH chroma, as CSS does, so they stay valid `#rrggbb`/`rgba()`
+//! output.
+
+use crate::element::{parse_angle, Color};
+
+/// Parse a CSS color (without `currentColor`).
+pub(crate) fn parse(s: &str) -> Option<Color> {
+    let s = s.trim();
+    if let Some(hex) = s.strip_prefix('#') {
+        return parse_hex(hex);
+    }
+
+    let lower = s.to_ascii_lowercase();
+    if let Some(open) = lower.find('(') {
+        let args = lower[open + 1..].strip_suffix(')')?;
+        return parse_function(lower[..open].trim_end(), args);
+    }
+
+    if lower == "transparent" {
+        return Some(Color::TRANSPARENT);
+    }
+    let index = NAMED_COLORS.binary_search_by_key(&lower.as_str(), |(name, _)| name).ok()?;
+    let [r, g, b] = NAMED_COLORS[index].1;
+    Some(Color::rgba(r, g, b, 255))
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|d| d * 17);
+    let byte = |i: usize| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok();
+    match hex.len() {
+        3 => Some(Color::rgba(digit(0)?, digit(1)?, digit(2)?, 255)),
+        4 => Some(Color::rgba(digit(0)?, digit(1)?, digit(2)?, digit(3)?)),
+        6 => Some(Color::rgba(byte(0)?, byte(1)?, byte(2)?, 255)),
+        8 => Some(Color::rgba(byte(0)?, byte(1)?, byte(2)?, byte(3)?)),
+        _ => None,
+    }
+}
+
+/// A color function argument; `none` counts as zero.
+#[derive(Clone, Copy)]
+enum Arg {
+    Number(f64),
+    Percent(f64),
+    Angle(f64),
+    None,
+}
+
+impl Arg {
+    fn parse(s: &str) -> Option<Self> {
+        if s == "none" {
+            return Some(Arg::None);
+        }
+        if let Some(pct) = s.strip_suffix('%') {
+            return pct.parse().ok().map(Arg::Percent);
+        }
+        if let Ok(number) = s.parse() {
+            return Some(Arg::Number(number));
+        }
+        parse_angle(s).map(|deg| Arg::Angle(deg as f64))
+    }
+
+    /// Value of a number, or of a percentage scaled so 100% is `full`.
+    fn value(self, full: f64) -> Option<f64> {
+        match self {
+            Arg::Number(n) => Some(n),
+            Arg::Percent(p) => Some(p / 100.0 * full),
+            Arg::None => Some(0.0),
+            Arg::Angle(_) => None,
+        }
+    }
+
+    /// Hue in degrees.
+    fn hue(self) -> Option<f64> {
+        match self {
+            Arg::Number(deg) | Arg::Angle(deg) => Some(deg.rem_euclid(360.0)),
+            Arg::None => Some(0.0),
+            Arg::Percent(_) => None,
+        }
+    }
+}
+
+/// Split function arguments into three channels and an optional alpha, in
+/// either `a, b, c[, alpha]` or `a b c[ / alpha]` form.
+fn split_args(args: &str) -> Option<([Arg; 3], Option<Arg>)> {
+    let (channels, alpha): (Vec<&str>, Option<&str>) = if args.contains(',') {
+        let mut parts: Vec<&str> = args.split(',').map(str::trim).collect();
+        let alpha = (parts.len() == 4).then(|| parts.pop()).flatten();
+        (parts, alpha)
+    } else {
+        let (channels, alpha) = match args.split_once('/') {
+            Some((channels, alpha)) => (channels, Some(alpha.trim())),
+            None => (args, None),
+        };
+        (channels.split_whitespace().collect(), alpha)
+    };
+
+    let [a, b, c] = channels.as_slice() else {
+        return None;
+    };
+    let alpha = alpha.map(Arg::parse).map_or(Some(None), |a| a.map(Some))?;
+    Some(([Arg::parse(a)?, Arg::parse(b)?, Arg::parse(c)?], alpha))
+}
+
+fn parse_function(name: &str, args: &str) -> Option<Color> {
+    if name == "color" {
+        return parse_color_function(args);
+    }
+
+    let ([a, b, c], alpha) = split_args(args)?;
+    let alpha = match alpha {
+        Some(alpha) => alpha.value(1.0)?.clamp(0.0, 1.0),
+        None => 1.0,
+    };
+
+    let srgb = match name {
+        "rgb" | "rgba" => [a.value(255.0)?, b.value(255.0)?, c.value(255.0)?].map(|v| v / 255.0),
+        "hsl" | "hsla" => hsl_to_srgb(a.hue()?, b.value(100.0)? / 100.0, c.value(100.0)? / 100.0),
+        "hwb" => hwb_to_srgb(a.hue()?, b.value(100.0)? / 100.0, c.value(100.0)? / 100.0),
+        "lab" => {
+            let xyz = lab_to_xyz_d50(a.value(100.0)?, b.value(125.0)?, c.value(125.0)?);
+            return Some(from_linear_srgb(xyz_d50_to_linear_srgb(xyz), alpha));
+        }
+        "lch" => {
+            let [l, a, b] = lch_to_lab(a.value(100.0)?, b.value(150.0)?, c.hue()?);
+            let xyz = lab_to_xyz_d50(l, a, b);
+            return Some(from_linear_srgb(xyz_d50_to_linear_srgb(xyz), alpha));
+        }
+        "oklab" => {
+            let lab = [a.value(1.0)?, b.value(0.4)?, c.value(0.4)?];
+            return Some(from_oklab(lab, alpha));
+        }
+        "oklch" => {
+            let lab = lch_to_lab(a.value(1.0)?, b.value(0.4)?, c.hue()?);
+            return Some(from_oklab(lab, alpha));
+        }
+        _ => return None,
+    };
+    Some(to_color(srgb, alpha))
+}
+
+/// `color(<space> c1 c2 c3 [/ alpha])` with one of the predefined spaces.
+fn parse_color_function(args: &str) -> Option<Color> {
+    let args = args.trim();
+    let (space, rest) = args.split_once(char::is_whitespace)?;
+    let ([a, b, c], alpha) = split_args(rest.trim())?;
+    let alpha = match alpha {
+        Some(alpha) => alpha.value(1.0)?.clamp(0.0, 1.0),
+        None => 1.0,
+    };
+    let channels = [a.value(1.0)?, b.value(1.0)?, c.value(1.0)?];
+
+    let linear = match space {
+        "srgb" => channels.map(srgb_to_linear),
+        "srgb-linear" => channels,
+        "display-p3" => xyz_d65_to_linear_srgb(mul(&P3_TO_XYZ, channels.map(srgb_to_linear))),
+        "a98-rgb" => xyz_d65_to_linear_srgb(mul(&A98_TO_XYZ, channels.map(a98_to_linear))),
+        "prophoto-rgb" => xyz_d50_to_linear_srgb(mul(&PROPHOTO_TO_XYZ_D50, channels.map(prophoto_to_linear))),
+        "rec2020" => xyz_d65_to_linear_srgb(mul(&REC2020_TO_XYZ, channels.map(rec2020_to_linear))),
+        "xyz" | "xyz-d65" => xyz_d65_to_linear_srgb(channels),
+        "xyz-d50" => xyz_d50_to_linear_srgb(channels),
+        _ => return None,
+    };
+    Some(from_linear_srgb(linear, alpha))
+}
+
+// ============================================================================
+// Conversions
+// ============================================================================
+
+type Matrix = [[f64; 3]; 3];
+
+const XYZ_TO_LINEAR_SRGB: Matrix = [
+    [3.2409699419045226, -1.537383177570094, -0.4986107602930034],
+    [-0.9692436362808796, 1.8759675015077202, 0.04155505740717559],
+    [0.05563007969699366, -0.20397695888897652, 1.0569715142428786],
+];
+
+/// Bradford chromatic adaptation from D50 to D65.
+const D50_TO_D65: Matrix = [
+    [0.955473421488075, -0.02309845494876471, 0.06325924320057072],
+    [-0.0283697093338637, 1.0099953980813041, 0.021041441191917323],
+    [0.012314014864481998, -0.020507649298898964, 1.330365926242124],
+];
+
+const P3_TO_XYZ: Matrix = [
+    [0.4865709486482162, 0.26566769316909306, 0.1982172852343625],
+    [0.2289745640697488, 0.6917385218365064, 0.079286914093745],
+    [0.0, 0.04511338185890264, 1.043944368900976],
+];
+
+const A98_TO_XYZ: Matrix = [
+    [0.5766690429101305, 0.1855582379065463, 0.1882286462349947],
+    [0.29734497525053605, 0.6273635662554661, 0.07529145849399788],
+    [0.02703136138641234, 0.07068885253582723, 0.9913375368376388],
+];
+
+const PROPHOTO_TO_XYZ_D50: Matrix = [
+    [0.7977666449006423, 0.13518129740053308, 0.0313477341283922],
+    [0.2880748288194013, 0.711835234241873, 0.00008993693872564],
+    [0.0, 0.0, 0.8251046025104602],
+];
+
+const REC2020_TO_XYZ: Matrix = [
+    [0.6369580483012914, 0.14461690358620832, 0.1688809751641721],
+    [0.2627002120112671, 0.6779980715188708, 0.05930171646986196],
+    [0.0, 0.028072693049087428, 1.060985057710791],
+];
+
+/// D50 reference white for CIE Lab.
+const D50_WHITE: [f64; 3] = [0.3457 / 0.3585, 1.0, (1.0 - 0.3457 - 0.3585) / 0.3585];
+
+/// Just noticeable difference in OKLab used by gamut mapping.
+const JND: f64 = 0.02;
+
+fn mul(m: &Matrix, v: [f64; 3]) -> [f64; 3] {
+    m.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+fn xyz_d65_to_linear_srgb(xyz: [f64; 3]) -> [f64; 3] {
+    mul(&XYZ_TO_LINEAR_SRGB, xyz)
+}
+
+fn xyz_d50_to_linear_srgb(xyz: [f64; 3]) -> [f64; 3] {
+    xyz_d65_to_linear_srgb(mul(&D50_TO_D65, xyz))
+}
+
+fn srgb_to_linear(c: f64) -> f64 {
+    let abs = c.abs();
+    if abs <= 0.04045 {
+        c / 12.92
+    } else {
+        c.signum() * ((abs + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f64) -> f64 {
+    let abs = c.abs();
+    if abs <= 0.0031308 {
+        c * 12.92
+    } else {
+        c.signum() * (1.055 * abs.powf(1.0 / 2.4) - 0.055)
+    }
+}
+
+fn a98_to_linear(c: f64) -> f64 {
+    c.signum() * c.abs().powf(563.0 / 256.0)
+}
+
+fn prophoto_to_linear(c: f64) -> f64 {
+    if c.abs() <= 16.0 / 512.0 {
+        c / 16.0
+    } else {
+        c.signum() * c.abs().powf(1.8)
+    }
+}
+
+fn rec2020_to_linear(c: f64) -> f64 {
+    const ALPHA: f64 = 1.09929682680944;
+    const BETA: f64 = 0.018053968510807;
+    if c.abs() < BETA * 4.5 {
+        c / 4.5
+    } else {
+        c.signum() * ((c.abs() + ALPHA - 1.0) / ALPHA).powf(1.0 / 0.45)
+    }
+}
+
+fn hsl_to_srgb(hue: f64, saturation: f64, lightness: f64) -> [f64; 3] {
+    let (s, l) = (saturation.clamp(0.0, 1.0), lightness.clamp(0.0, 1.0));
+    let channel = |n: f64| {
+        let k = (n + hue / 30.0) % 12.0;
+        let a = s * l.min(1.0 - l);
+        l - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+    };
+    [channel(0.0), channel(8.0), channel(4.0)]
+}
+
+fn hwb_to_srgb(hue: f64, whiteness: f64, blackness: f64) -> [f64; 3] {
+    let (w, b) = (whiteness.clamp(0.0, 1.0), blackness.clamp(0.0, 1.0));
+    if w + b >= 1.0 {
+        let gray = w / (w + b);
+        return [gray; 3];
+    }
+    hsl_to_srgb(hue, 1.0, 0.5).map(|c| c * (1.0 - w - b) + w)
+}
+
+fn lab_to_xyz_d50(l: f64, a: f64, b: f64) -> [f64; 3] {
+    const KAPPA: f64 = 24389.0 / 27.0;
+    const EPSILON: f64 = 216.0 / 24389.0;
+    let f1 = (l + 16.0) / 116.0;
+    let f0 = a / 500.0 + f1;
+    let f2 = f1 - b / 200.0;
+    let component = |f: f64| {
+        if f.powi(3) > EPSILON {
+            f.powi(3)
+        } else {
+            (116.0 * f - 16.0) / KAPPA
+        }
+    };
+    let y = if l > KAPPA * EPSILON { f1.powi(3) } else { l / KAPPA };
+    [component(f0) * D50_WHITE[0], y, component(f2) * D50_WHITE[2]]
+}
+
+/// Polar (lightness, chroma, hue) to rectangular (lightness, a, b).
+fn lch_to_lab(l: f64, chroma: f64, hue: f64) -> [f64; 3] {
+    let (sin, cos) = hue.to_radians().sin_cos();
+    let chroma = chroma.max(0.0);
+    [l, chroma * cos, chroma * sin]
+}
+
+fn oklab_to_linear_srgb([l, a, b]: [f64; 3]) -> [f64; 3] {
+    let l_ = (l + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+    let m_ = (l - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+    let s_ = (l - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+    [
+        4.0767416621 * l_ - 3.3077115913 * m_ + 0.2309699292 * s_,
+        -1.2684380046 * l_ + 2.6097574011 * m_ - 0.3413193965 * s_,
+        -0.0041960863 * l_ - 0.7034186147 * m_ + 1.7076147010 * s_,
+    ]
+}
+
+fn linear_srgb_to_oklab([r, g, b]: [f64; 3]) -> [f64; 3] {
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+    [
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    ]
+}
+
+fn in_gamut(srgb: [f64; 3]) -> bool {
+    const EPSILON: f64 = 1e-4;
+    srgb.iter().all(|c| (-EPSILON..=1.0 + EPSILON).contains(c))
+}
+
+fn clip(srgb: [f64; 3]) -> [f64; 3] {
+    srgb.map(|c| c.clamp(0.0, 1.0))
+}
+
+fn delta_eok(a: [f64; 3], b: [f64; 3]) -> f64 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+fn from_linear_srgb(linear: [f64; 3], alpha: f64) -> Color {
+    let srgb = linear.map(linear_to_srgb);
+    if in_gamut(srgb) {
+        return to_color(srgb, alpha);
+    }
+    from_oklab(linear_srgb_to_oklab(linear), alpha)
+}
+
+/// Convert OKLab to sRGB, reducing chroma at constant lightness and hue
+/// until the color is within a just noticeable difference of sRGB (the CSS
+/// Color 4 gamut mapping algorithm).
+fn from_oklab(lab: [f64; 3], alpha: f64) -> Color {
+    let to_srgb = |lab: [f64; 3]| oklab_to_linear_srgb(lab).map(linear_to_srgb);
+    let [l, a, b] = lab;
+    if l >= 1.0 {
+        return to_color([1.0; 3], alpha);
+    }
+    if l <= 0.0 {
+        return to_color([0.0; 3], alpha);
+    }
+
+    let origin = to_srgb(lab);
+    if in_gamut(origin) {
+        return to_color(origin, alpha);
+    }
+
+    let oklab_of = |srgb: [f64; 3]| linear_srgb_to_oklab(srgb.map(srgb_to_linear));
+    let clipped = clip(origin);
+    if delta_eok(oklab_of(clipped), lab) < JND {
+        return to_color(clipped, alpha);
+    }
+
+    let chroma = a.hypot(b);
+    let (hue_a, hue_b) = (a / chroma, b / chroma);
+    let at = |c: f64| [l, c * hue_a, c * hue_b];
+    let (mut min, mut max) = (0.0, chroma);
+    let mut min_in_gamut = true;
+    let mut current = clipped;
+    while max - min > 1e-4 {
+        let c = (min + max) / 2.0;
+        let candidate = to_srgb(at(c));
+        if min_in_gamut && in_gamut(candidate) {
+            min = c;
+            continue;
+        }
+        current = clip(candidate);
+        let e = delta_eok(oklab_of(current), at(c));
+        if e < JND {
+            if JND - e < 1e-4 {
+                break;
+            }
+            min_in_gamut = false;
+            min = c;
+        } else {
+            max = c;
+        }
+    }
+    to_color(current, alpha)
+}
+
+fn to_color(srgb: [f64; 3], alpha: f64) -> Color {
+    let [r, g, b] = clip(srgb).map(|c| (c * 255.0).round() as u8);
+    Color::rgba(r, g, b, (alpha.clamp(0.0, 1.0) * 255.0) as u8)
+}
+
+// ============================================================================
+// Named colors
+// ============================================================================
+
+/// CSS named colors, sorted by name.
+const NAMED_COLORS: &[(&str, [u8; 3])] = &[
+    ("aliceblue", [240, 248, 255]),
+    ("antiquewhite", [250, 235, 215]),
+    ("aqua", [0, 255, 255]),
+    ("aquamarine", [127, 255, 212]),
+    ("azure", [240, 255, 255]),
+    ("beige", [245, 245, 220]),
+    ("bisque", [255, 228, 196]),
+    ("black", [0, 0, 0]),
+    ("blanchedalmond", [255, 235, 205]),
+    ("blue", [0, 0, 255]),
+    ("blueviolet", [138, 43, 226]),
+    ("brown", [165, 42, 42]),
+    ("burlywood", [222, 184, 135]),
+    ("cadetblue", [95, 158, 160]),
+    ("chartreuse", [127, 255, 0]),
+    ("chocolate", [210, 105, 30]),
+    ("coral", [255, 127, 80]),
+    ("cornflowerblue", [100, 149, 237]),
+    ("cornsilk", [255, 248, 220]),
+    ("crimson", [220, 20, 60]),
+    ("cyan", [0, 255, 255]),
+    ("darkblue", [0, 0, 139]),
+    ("darkcyan", [0, 139, 139]),
+    ("darkgoldenrod", [184, 134, 11]),
+    ("darkgray", [169, 169, 169]),
+    ("darkgreen", [0, 100, 0]),
+    ("darkgrey", [169, 169, 169]),
+    ("darkkhaki", [189, 183, 107]),
+    ("darkmagenta", [139, 0, 139]),
+    ("darkolivegreen", [85, 107, 47]),
+    ("darkorange", [255, 140, 0]),
+    ("darkorchid", [153, 50, 204]),
+    ("darkred", [139, 0, 0]),
+    ("darksalmon", [233, 150, 122]),
+    ("darkseagreen", [143, 188, 143]),
+    ("darkslateblue", [72, 61, 139]),
+    ("darkslategray", [47, 79, 79]),
+    ("darkslategrey", [47, 79, 79]),
+    ("darkturquoise", [0, 206, 209]),
+    ("darkviolet", [148, 0, 211]),
+    ("deeppink", [255, 20, 147]),
+    ("deepskyblue", [0, 191, 255]),
+    ("dimgray", [105, 105, 105]),
+    ("dimgrey", [105, 105, 105]),
+    ("dodgerblue", [30, 144, 255]),
+    ("firebrick", [178, 34, 34]),
+    ("floralwhite", [255, 250, 240]),
+    ("forestgreen", [34, 139, 34]),
+    ("fuchsia", [255, 0, 255]),
+    ("gainsboro", [220, 220, 220]),
+    ("ghostwhite", [248, 248, 255]),
+    ("gold", [255, 215, 0]),
+    ("goldenrod", [218, 165, 32]),
+    ("gray", [128, 128, 128]),
+    ("green", [0, 128, 0]),
+    ("greenyellow", [173, 255, 47]),
+    ("grey", [128, 128, 128]),
+    ("honeydew", [240, 255, 240]),
+    ("hotpink", [255, 105, 180]),
+    ("indianred", [205, 92, 92]),
+    ("indigo", [75, 0, 130]),
+    ("ivory", [255, 255, 240]),
+    ("khaki", [240, 230, 140]),
+    ("lavender", [230, 230, 250]),
+    ("lavenderblush", [255, 240, 245]),
+    ("lawngreen", [124, 252, 0]),
+    ("lemonchiffon", [255, 250, 205]),
+    ("lightblue", [173, 216, 230]),
+    ("lightcoral", [240, 128, 128]),
+    ("lightcyan", [224, 255, 255]),
+    ("lightgoldenrodyellow", [250, 250, 210]),
+    ("lightgray", [211, 211, 211]),
+    ("lightgreen", [144, 238, 144]),
+    ("lightgrey", [211, 211, 211]),
+    ("lightpink", [255, 182, 193]),
+    ("lightsalmon", [255, 160, 122]),
+    ("lightseagreen", [32, 178, 170]),
+    ("lightskyblue", [135, 206, 250]),
+    ("lightslategray", [119, 136, 153]),
+    ("lightslategrey", [119, 136, 153]),
+    ("lightsteelblue", [176, 196, 222]),
+    ("lightyellow", [255, 255, 224]),
+    ("lime", [0, 255, 0]),
+    ("limegreen", [50, 205, 50]),
+    ("linen", [250, 240, 230]),
+    ("magenta", [255, 0, 255]),
+    ("maroon", [128, 0, 0]),
+    ("mediumaquamarine", [102, 205, 170]),
+    ("mediumblue", [0, 0, 205]),
+    ("mediumorchid", [186, 85, 211]),
+    ("mediumpurple", [147, 112, 219]),
+    ("mediumseagreen", [60, 179, 113]),
+    ("mediumslateblue", [123, 104, 238]),
+    ("mediumspringgreen", [0, 250, 154]),
+    ("mediumturquoise", [72, 209, 204]),
+    ("mediumvioletred", [199, 21, 133]),
+    ("midnightblue", [25, 25, 112]),
+    ("mintcream", [245, 255, 250]),
+    ("mistyrose", [255, 228, 225]),
+    ("moccasin", [255, 228, 181]),
+    ("navajowhite", [255, 222, 173]),
+    ("navy", [0, 0, 128]),
+    ("oldlace", [253, 245, 230]),
+    ("olive", [128, 128, 0]),
+    ("olivedrab", [107, 142, 35]),
+    ("orange", [255, 165, 0]),
+    ("orangered", [255, 69, 0]),
+    ("orchid", [218, 112, 214]),
+    ("palegoldenrod", [238, 232, 170]),
+    ("palegreen", [152, 251, 152]),
+    ("paleturquoise", [175, 238, 238]),
+    ("palevioletred", [219, 112, 147]),
+    ("papayawhip", [255, 239, 213]),
+    ("peachpuff", [255, 218, 185]),
+    ("peru", [205, 133, 63]),
+    ("pink", [255, 192, 203]),
+    ("plum", [221, 160, 221]),
+    ("powderblue", [176, 224, 230]),
+    ("purple", [128, 0, 128]),
+    ("rebeccapurple", [102, 51, 153]),
+    ("red", [255, 0, 0]),
+    ("rosybrown", [188, 143, 143]),
+    ("royalblue", [65, 105, 225]),
+    ("saddlebrown", [139, 69, 19]),
+    ("salmon", [250, 128, 114]),
+    ("sandybrown", [244, 164, 96]),
+    ("seagreen", [46, 139, 87]),
+    ("seashell", [255, 245, 238]),
+    ("sienna", [160, 82, 45]),
+    ("silver", [192, 192, 192]),
+    ("skyblue", [135, 206, 235]),
+    ("slateblue", [106, 90, 205]),
+    ("slategray", [112, 128, 144]),
+    ("slategrey", [112, 128, 144]),
+    ("snow", [255, 250, 250]),
+    ("springgreen", [0, 255, 127]),
+    ("steelblue", [70, 130, 180]),
+    ("tan", [210, 180, 140]),
+    ("teal", [0, 128, 128]),
+    ("thistle", [216, 191, 216]),
+    ("tomato", [255, 99, 71]),
+    ("turquoise", [64, 224, 208]),
+    ("violet", [238, 130, 238]),
+    ("wheat", [245, 222, 179]),
+    ("white", [255, 255, 255]),
+    ("whitesmoke", [245, 245, 245]),
+    ("yellow", [255, 255, 0]),
+    ("yellowgreen", [154, 205, 50]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgba(s: &str) -> (u8, u8, u8, u8) {
+        let c = parse(s).unwrap_or_else(|| panic!("failed to parse {}", s));
+        (c.r, c.g, c.b, c.a)
+    }
+
+    #[test]
+    fn test_named_colors_sorted() {
+        assert!(NAMED_COLORS.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(rgba("RebeccaPurple"), (102, 51, 153, 255));
+        assert!(parse("notacolor").is_none());
+    }
+
+    #[test]
+    fn test_rgb_syntaxes() {
+        assert_eq!(rgba("#f008"), (255, 0, 0, 136));
+        assert_eq!(rgba("rgba(255, 0, 0, 0.5)"), (255, 0, 0, 127));
+        assert_eq!(rgba("rgb(100% 50% 0% / 25%)"), (255, 128, 0, 63));
+        assert_eq!(rgba("rgb(none 255 0)"), (0, 255, 0, 255));
+        assert!(parse("rgb(1 2)").is_none());
+    }
+
+    #[test]
+    fn test_hsl_and_hwb() {
+        assert_eq!(rgba("hsl(210 40% 50% / 0.8)"), (77, 128, 179, 204));
+        assert_eq!(rgba("hsl(120deg, 100%, 25%)"), (0, 128, 0, 255));
+        assert_eq!(rgba("hsl(0.5turn 100% 50%)"), (0, 255, 255, 255));
+        assert_eq!(rgba("hwb(0 0% 0%)"), (255, 0, 0, 255));
+        assert_eq!(rgba("hwb(90 60% 60%)"), (128, 128, 128, 255));
+    }
+
+    #[test]
+    fn test_lab_spaces() {
+        assert_eq!(rgba("oklch(62.8% 0.2577 29.23)"), (255, 0, 0, 255));
+        assert_eq!(rgba("oklab(1 0 0)"), (255, 255, 255, 255));
+        assert_eq!(rgba("lab(54.29 80.8 69.89)"), (255, 0, 0, 255));
+        assert_eq!(rgba("lch(54.29 106.84 40.85)"), (255, 0, 0, 255));
+        assert_eq!(rgba("color(srgb 1 0.5 0)"), (255, 128, 0, 255));
+    }
+
+    #[test]
+    fn test_gamut_mapping() {
+        // Display P3 red is outside sRGB and maps to a saturated red
+        let (r, g, b, _) = rgba("color(display-p3 1 0 0)");
+        assert!(r == 255 && g < 60 && b < 60);
+        // A very high chroma keeps its lightness instead of clipping to black
+        let (r, g, b, _) = rgba("oklch(0.7 0.5 150)");
+        assert!(g > r && g > b && g > 150);
+    }
+}
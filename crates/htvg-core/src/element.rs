@@ -2,9 +2,11 @@
 //!
 //! Defines the JSON element tree structure that gets deserialized and rendered to SVG.
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-/// Root element type - can be Box, Flex, Text, or Image.
+use crate::color;
+
+/// Root element type - can be Box, Flex, Grid, Text, or Image.
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum Element {
@@ -22,9 +24,17 @@ pub enum Element {
         #[serde(default)]
         children: Vec<Element>,
     },
-    /// Text leaf element
+    /// Grid container element
+    Grid {
+        #[serde(default)]
+        style: GridStyle,
+        #[serde(default)]
+        children: Vec<Element>,
+    },
+    /// Text leaf element, either a plain `content` string or styled `spans`
     Text {
-        content: String,
+        #[serde(flatten)]
+        content: TextContent,
         #[serde(default)]
         style: TextStyle,
     },
@@ -38,119 +48,644 @@ pub enum Element {
     },
 }
 
+/// An inline run of text with its own style inside a text element.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TextSpan {
+    pub content: String,
+    #[serde(default)]
+    pub style: SpanStyle,
+}
+
+/// The text of a text element: exactly one of `content` or `spans`.
+#[derive(Debug, Clone)]
+pub enum TextContent {
+    Plain(String),
+    Spans(Vec<TextSpan>),
+}
+
+impl<'de> Deserialize<'de> for TextContent {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Fields {
+            content: Option<String>,
+            spans: Option<Vec<TextSpan>>,
+        }
+
+        let fields = Fields::deserialize(deserializer)?;
+        match (fields.content, fields.spans) {
+            (Some(content), None) => Ok(TextContent::Plain(content)),
+            (None, Some(spans)) => Ok(TextContent::Spans(spans)),
+            (Some(_), Some(_)) => Err(serde::de::Error::custom("text has both `content` and `spans`")),
+            (None, None) => Err(serde::de::Error::custom("text needs `content` or `spans`")),
+        }
+    }
+}
+
 // ============================================================================
 // Dimension types
 // ============================================================================
 
-/// A dimension value that can be a number (pixels) or percentage string.
-#[derive(Debug, Clone, Deserialize)]
-#[serde(untagged)]
-pub enum Dimension {
-    Px(f32),
-    Percent(String), // e.g., "50%"
+/// A CSS length: a number (pixels), a string with a `px`, `%`, `em`, `rem`,
+/// `vw` or `vh` unit, or a `calc()` expression (e.g. `"calc(100% - 2em)"`).
+/// Each field holds the amount of one unit; sums stay unresolved until layout
+/// knows the font sizes, viewport and containing block.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Dimension {
+    pub px: f32,
+    pub percent: f32,
+    pub em: f32,
+    pub rem: f32,
+    pub vw: f32,
+    pub vh: f32,
 }
 
 impl Dimension {
-    /// Convert to pixels given a container size for percentages.
-    pub fn to_px(&self, container_size: f32) -> f32 {
-        match self {
-            Dimension::Px(px) => *px,
-            Dimension::Percent(s) => {
-                let percent = s.trim_end_matches('%').parse::<f32>().unwrap_or(0.0);
-                container_size * percent / 100.0
+    pub fn px(px: f32) -> Self {
+        Self { px, ..Default::default() }
+    }
+
+    pub fn percent(percent: f32) -> Self {
+        Self { percent, ..Default::default() }
+    }
+
+    /// Parse a number with an optional unit, or a `calc()` expression.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if let Some(expr) = function_args(s, "calc") {
+            let mut parser = CalcParser { rest: expr };
+            return match parser.sum()? {
+                CalcValue::Length(length) if parser.rest.trim().is_empty() => Some(length),
+                _ => None,
+            };
+        }
+        // Unitless numbers are pixels
+        match parse_number(s) {
+            Some(px) => Some(Self::px(px)),
+            None => parse_length_unit(s),
+        }
+    }
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            px: self.px + other.px,
+            percent: self.percent + other.percent,
+            em: self.em + other.em,
+            rem: self.rem + other.rem,
+            vw: self.vw + other.vw,
+            vh: self.vh + other.vh,
+        }
+    }
+
+    fn scale(self, factor: f32) -> Self {
+        Self {
+            px: self.px * factor,
+            percent: self.percent * factor,
+            em: self.em * factor,
+            rem: self.rem * factor,
+            vw: self.vw * factor,
+            vh: self.vh * factor,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Dimension {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match NumberOrString::deserialize(deserializer)? {
+            NumberOrString::Number(px) => Ok(Dimension::px(px)),
+            NumberOrString::String(s) => {
+                Dimension::parse(&s).ok_or_else(|| serde::de::Error::custom(format!("invalid length: {}", s)))
             }
         }
     }
 }
 
-/// Spacing value for margin/padding - can be a single number or space-separated string.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Deserialize)]
 #[serde(untagged)]
-pub enum Spacing {
-    Uniform(f32),
-    Multi(String), // e.g., "10 20" or "10 20 30 40"
+enum NumberOrString {
+    Number(f32),
+    String(String),
 }
 
-impl Default for Spacing {
-    fn default() -> Self {
-        Spacing::Uniform(0.0)
+fn parse_number(s: &str) -> Option<f32> {
+    s.parse().ok().filter(|v: &f32| v.is_finite())
+}
+
+/// Parse a number with a length unit (`10px`, `50%`, `1.5em`, ...).
+fn parse_length_unit(s: &str) -> Option<Dimension> {
+    // "rem" must be tried before "em"
+    let units = ["px", "%", "rem", "em", "vw", "vh"];
+    let (unit, value) = units
+        .iter()
+        .find_map(|unit| Some((*unit, parse_number(s.strip_suffix(unit)?)?)))?;
+    let mut dim = Dimension::default();
+    match unit {
+        "px" => dim.px = value,
+        "%" => dim.percent = value,
+        "rem" => dim.rem = value,
+        "em" => dim.em = value,
+        "vw" => dim.vw = value,
+        _ => dim.vh = value,
     }
+    Some(dim)
 }
 
-impl Spacing {
-    /// Parse into [top, right, bottom, left] values.
-    pub fn to_edges(&self) -> [f32; 4] {
-        match self {
-            Spacing::Uniform(v) => [*v, *v, *v, *v],
-            Spacing::Multi(s) => {
-                let parts: Vec<f32> = s
-                    .split_whitespace()
-                    .filter_map(|p| p.parse().ok())
-                    .collect();
-                match parts.len() {
-                    1 => [parts[0], parts[0], parts[0], parts[0]],
-                    2 => [parts[0], parts[1], parts[0], parts[1]], // vertical, horizontal
-                    3 => [parts[0], parts[1], parts[2], parts[1]], // top, horizontal, bottom
-                    4 => [parts[0], parts[1], parts[2], parts[3]], // top, right, bottom, left
-                    _ => [0.0, 0.0, 0.0, 0.0],
+/// A `calc()` operand or result.
+enum CalcValue {
+    Number(f32),
+    Length(Dimension),
+}
+
+/// Recursive descent parser for `calc()` sums and products. Lengths can only
+/// be multiplied or divided by plain numbers, so every result stays a sum of
+/// units.
+struct CalcParser<'a> {
+    rest: &'a str,
+}
+
+impl CalcParser<'_> {
+    fn sum(&mut self) -> Option<CalcValue> {
+        let mut value = self.product()?;
+        loop {
+            let sign = match self.rest.trim_start().chars().next() {
+                Some('+') => 1.0,
+                Some('-') => -1.0,
+                _ => return Some(value),
+            };
+            self.rest = &self.rest.trim_start()[1..];
+            value = match (value, self.product()?) {
+                (CalcValue::Number(a), CalcValue::Number(b)) => CalcValue::Number(a + sign * b),
+                (CalcValue::Length(a), CalcValue::Length(b)) => CalcValue::Length(a.add(b.scale(sign))),
+                _ => return None,
+            };
+        }
+    }
+
+    fn product(&mut self) -> Option<CalcValue> {
+        let mut value = self.operand()?;
+        loop {
+            let divide = match self.rest.trim_start().chars().next() {
+                Some('*') => false,
+                Some('/') => true,
+                _ => return Some(value),
+            };
+            self.rest = &self.rest.trim_start()[1..];
+            value = match (value, self.operand()?) {
+                (_, CalcValue::Number(b)) if divide && b == 0.0 => return None,
+                (CalcValue::Number(a), CalcValue::Number(b)) => {
+                    CalcValue::Number(if divide { a / b } else { a * b })
                 }
+                (CalcValue::Length(a), CalcValue::Number(b)) => {
+                    CalcValue::Length(a.scale(if divide { 1.0 / b } else { b }))
+                }
+                (CalcValue::Number(a), CalcValue::Length(b)) if !divide => CalcValue::Length(b.scale(a)),
+                _ => return None,
+            };
+        }
+    }
+
+    fn operand(&mut self) -> Option<CalcValue> {
+        self.rest = self.rest.trim_start();
+        let nested = self.rest.strip_prefix("calc(").or_else(|| self.rest.strip_prefix('('));
+        if let Some(rest) = nested {
+            self.rest = rest;
+            let value = self.sum()?;
+            self.rest = self.rest.trim_start().strip_prefix(')')?;
+            return Some(value);
+        }
+
+        // A token runs until whitespace, a parenthesis or an operator; a
+        // leading sign belongs to the number
+        let end = self
+            .rest
+            .char_indices()
+            .find(|&(i, c)| c.is_whitespace() || "()*/".contains(c) || (i > 0 && "+-".contains(c)))
+            .map_or(self.rest.len(), |(i, _)| i);
+        let (token, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        match parse_number(token) {
+            Some(number) => Some(CalcValue::Number(number)),
+            None => parse_length_unit(token).map(CalcValue::Length),
+        }
+    }
+}
+
+/// Spacing value for margin/padding - a single length or a space-separated
+/// string of 1-4 lengths (e.g. `"10 20"` or `"1em 2em 0 calc(10% + 4px)"`).
+/// Holds [top, right, bottom, left].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Spacing(pub [Dimension; 4]);
+
+impl Spacing {
+    pub fn uniform(value: Dimension) -> Self {
+        Spacing([value; 4])
+    }
+
+    /// Parse CSS shorthand: 1-4 values in top, right, bottom, left order.
+    pub fn parse(s: &str) -> Option<Self> {
+        let parts = split_top_level(s, char::is_whitespace)
+            .into_iter()
+            .map(Dimension::parse)
+            .collect::<Option<Vec<_>>>()?;
+        expand_edges(&parts).map(Spacing)
+    }
+}
+
+impl<'de> Deserialize<'de> for Spacing {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match NumberOrString::deserialize(deserializer)? {
+            NumberOrString::Number(px) => Ok(Spacing::uniform(Dimension::px(px))),
+            NumberOrString::String(s) => {
+                Spacing::parse(&s).ok_or_else(|| serde::de::Error::custom(format!("invalid spacing: {}", s)))
             }
         }
     }
 }
 
-/// Border radius - single value or per-corner.
-#[derive(Debug, Clone, Deserialize)]
-#[serde(untagged)]
-pub enum BorderRadius {
-    Uniform(f32),
-    Multi(String), // e.g., "8 8 0 0" for top-left, top-right, bottom-right, bottom-left
+/// A length or `auto`, for margins and `flexBasis`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DimensionOrAuto {
+    Auto,
+    Length(Dimension),
 }
 
-impl Default for BorderRadius {
+impl Default for DimensionOrAuto {
     fn default() -> Self {
-        BorderRadius::Uniform(0.0)
+        DimensionOrAuto::Length(Dimension::default())
+    }
+}
+
+impl DimensionOrAuto {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "auto" => Some(DimensionOrAuto::Auto),
+            s => Dimension::parse(s).map(DimensionOrAuto::Length),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for DimensionOrAuto {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match NumberOrString::deserialize(deserializer)? {
+            NumberOrString::Number(px) => Ok(DimensionOrAuto::Length(Dimension::px(px))),
+            NumberOrString::String(s) => DimensionOrAuto::parse(&s)
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid length: {}", s))),
+        }
+    }
+}
+
+/// Margin - like [`Spacing`], but sides can be `auto` (e.g. `"0 auto"`) to
+/// take up free space in flex and grid layout or center a block.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Margin(pub [DimensionOrAuto; 4]);
+
+impl Margin {
+    /// Parse CSS shorthand: 1-4 values in top, right, bottom, left order.
+    pub fn parse(s: &str) -> Option<Self> {
+        let parts = split_top_level(s, char::is_whitespace)
+            .into_iter()
+            .map(DimensionOrAuto::parse)
+            .collect::<Option<Vec<_>>>()?;
+        expand_edges(&parts).map(Margin)
+    }
+}
+
+impl<'de> Deserialize<'de> for Margin {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match NumberOrString::deserialize(deserializer)? {
+            NumberOrString::Number(px) => Ok(Margin([DimensionOrAuto::Length(Dimension::px(px)); 4])),
+            NumberOrString::String(s) => {
+                Margin::parse(&s).ok_or_else(|| serde::de::Error::custom(format!("invalid margin: {}", s)))
+            }
+        }
     }
 }
 
+/// Expand 1-4 CSS shorthand values into [top, right, bottom, left].
+fn expand_edges<T: Copy>(parts: &[T]) -> Option<[T; 4]> {
+    match *parts {
+        [all] => Some([all; 4]),
+        [vertical, horizontal] => Some([vertical, horizontal, vertical, horizontal]),
+        [top, horizontal, bottom] => Some([top, horizontal, bottom, horizontal]),
+        [top, right, bottom, left] => Some([top, right, bottom, left]),
+        _ => None,
+    }
+}
+
+/// Border radius - a single length or 1-4 space-separated lengths. Holds
+/// [top-left, top-right, bottom-right, bottom-left]; percentages are of the
+/// box's shorter side.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BorderRadius(pub [Dimension; 4]);
+
 impl BorderRadius {
-    /// Parse into [top-left, top-right, bottom-right, bottom-left] values.
-    pub fn to_corners(&self) -> [f32; 4] {
-        match self {
-            BorderRadius::Uniform(v) => [*v, *v, *v, *v],
-            BorderRadius::Multi(s) => {
-                let parts: Vec<f32> = s
-                    .split_whitespace()
-                    .filter_map(|p| p.parse().ok())
-                    .collect();
-                match parts.len() {
-                    1 => [parts[0], parts[0], parts[0], parts[0]],
-                    2 => [parts[0], parts[1], parts[0], parts[1]],
-                    4 => [parts[0], parts[1], parts[2], parts[3]],
-                    _ => [0.0, 0.0, 0.0, 0.0],
+    /// Parse CSS shorthand: 1-4 values starting at the top-left corner.
+    pub fn parse(s: &str) -> Option<Self> {
+        let parts = split_top_level(s, char::is_whitespace)
+            .into_iter()
+            .map(Dimension::parse)
+            .collect::<Option<Vec<_>>>()?;
+        match parts[..] {
+            [all] => Some(BorderRadius([all; 4])),
+            [a, b] => Some(BorderRadius([a, b, a, b])),
+            [top_left, b, bottom_right] => Some(BorderRadius([top_left, b, bottom_right, b])),
+            [top_left, top_right, bottom_right, bottom_left] => {
+                Some(BorderRadius([top_left, top_right, bottom_right, bottom_left]))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for BorderRadius {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match NumberOrString::deserialize(deserializer)? {
+            NumberOrString::Number(px) => Ok(BorderRadius([Dimension::px(px); 4])),
+            NumberOrString::String(s) => BorderRadius::parse(&s)
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid border radius: {}", s))),
+        }
+    }
+}
+
+// ============================================================================
+// Grid types
+// ============================================================================
+
+/// A grid track list, e.g. `"200px 1fr repeat(3, minmax(50px, 1fr))"`.
+#[derive(Debug, Clone, Default)]
+pub struct GridTemplate(pub Vec<GridTrack>);
+
+/// A single entry in a grid track list.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GridTrack {
+    Single(TrackSize),
+    Repeat(RepeatCount, Vec<TrackSize>),
+}
+
+/// Number of repetitions in `repeat()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RepeatCount {
+    Count(u16),
+    AutoFill,
+    AutoFit,
+}
+
+/// Track sizing function as `minmax(min, max)`. A plain breadth sets both,
+/// except `fr`, which behaves like `minmax(auto, Nfr)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackSize {
+    pub min: TrackBreadth,
+    pub max: TrackBreadth,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrackBreadth {
+    Px(f32),
+    Percent(f32),
+    Fr(f32),
+    Auto,
+    MinContent,
+    MaxContent,
+}
+
+impl GridTemplate {
+    /// Parse a CSS track list.
+    pub fn parse(s: &str) -> Option<Self> {
+        let tracks = split_top_level(s, char::is_whitespace)
+            .into_iter()
+            .map(|token| match function_args(token, "repeat") {
+                Some(args) => {
+                    let (count, tracks) = args.split_once(',')?;
+                    let count = match count.trim() {
+                        "auto-fill" => RepeatCount::AutoFill,
+                        "auto-fit" => RepeatCount::AutoFit,
+                        n => RepeatCount::Count(n.parse().ok().filter(|&n| n > 0)?),
+                    };
+                    let tracks = split_top_level(tracks, char::is_whitespace)
+                        .into_iter()
+                        .map(TrackSize::parse)
+                        .collect::<Option<Vec<_>>>()?;
+                    (!tracks.is_empty()).then_some(GridTrack::Repeat(count, tracks))
+                }
+                None => TrackSize::parse(token).map(GridTrack::Single),
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self(tracks))
+    }
+}
+
+impl TrackSize {
+    fn parse(s: &str) -> Option<Self> {
+        if let Some(args) = function_args(s, "minmax") {
+            let (min, max) = args.split_once(',')?;
+            let min = TrackBreadth::parse(min.trim())?;
+            if matches!(min, TrackBreadth::Fr(_)) {
+                return None;
+            }
+            return Some(Self {
+                min,
+                max: TrackBreadth::parse(max.trim())?,
+            });
+        }
+
+        let breadth = TrackBreadth::parse(s)?;
+        Some(match breadth {
+            TrackBreadth::Fr(_) => Self {
+                min: TrackBreadth::Auto,
+                max: breadth,
+            },
+            _ => Self {
+                min: breadth,
+                max: breadth,
+            },
+        })
+    }
+}
+
+impl TrackBreadth {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "auto" => Some(Self::Auto),
+            "min-content" => Some(Self::MinContent),
+            "max-content" => Some(Self::MaxContent),
+            _ => {
+                if let Some(n) = s.strip_suffix("fr") {
+                    n.parse().ok().filter(|&n: &f32| n >= 0.0).map(Self::Fr)
+                } else if let Some(n) = s.strip_suffix('%') {
+                    n.parse().ok().map(Self::Percent)
+                } else {
+                    s.strip_suffix("px").unwrap_or(s).parse().ok().map(Self::Px)
                 }
             }
         }
     }
 }
 
+impl<'de> Deserialize<'de> for GridTemplate {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        GridTemplate::parse(&s)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid grid template: {}", s)))
+    }
+}
+
+/// Grid item placement on one axis, e.g. `"1 / 3"`, `"span 2"` or `2`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GridLine {
+    pub start: GridPlacement,
+    pub end: GridPlacement,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum GridPlacement {
+    #[default]
+    Auto,
+    /// 1-based line index; negative values count from the end.
+    Line(i16),
+    Span(u16),
+}
+
+impl GridLine {
+    /// Parse a CSS `grid-row`/`grid-column` value.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.split_once('/') {
+            Some((start, end)) => Some(Self {
+                start: GridPlacement::parse(start.trim())?,
+                end: GridPlacement::parse(end.trim())?,
+            }),
+            None => Some(Self {
+                start: GridPlacement::parse(s.trim())?,
+                end: GridPlacement::Auto,
+            }),
+        }
+    }
+}
+
+impl GridPlacement {
+    fn parse(s: &str) -> Option<Self> {
+        if s == "auto" {
+            return Some(Self::Auto);
+        }
+        if let Some(n) = s.strip_prefix("span") {
+            return n.trim().parse().ok().filter(|&n| n > 0).map(Self::Span);
+        }
+        s.parse().ok().filter(|&n| n != 0).map(Self::Line)
+    }
+}
+
+impl<'de> Deserialize<'de> for GridLine {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum GridLineValue {
+            Number(i16),
+            String(String),
+        }
+
+        let parsed = match GridLineValue::deserialize(deserializer)? {
+            GridLineValue::Number(n) => GridLine::parse(&n.to_string()),
+            GridLineValue::String(s) => GridLine::parse(&s),
+        };
+        parsed.ok_or_else(|| serde::de::Error::custom("invalid grid line"))
+    }
+}
+
+/// Split `s` on separators that aren't nested inside parentheses.
+fn split_top_level(s: &str, is_separator: impl Fn(char) -> bool) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            c if depth == 0 && is_separator(c) => {
+                parts.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts.into_iter().map(str::trim).filter(|p| !p.is_empty()).collect()
+}
+
+/// Return the argument string of a CSS function call like `name(args)`.
+fn function_args<'a>(s: &'a str, name: &str) -> Option<&'a str> {
+    s.strip_prefix(name)?.trim_start().strip_prefix('(')?.strip_suffix(')')
+}
+
+/// Parse a CSS angle (`deg`, `grad`, `rad`, `turn` or unitless zero) into degrees.
+pub(crate) fn parse_angle(s: &str) -> Option<f32> {
+    if s == "0" {
+        return Some(0.0);
+    }
+    // "grad" must be tried before "rad"
+    let units = [("deg", 1.0), ("grad", 0.9), ("rad", 180.0 / std::f32::consts::PI), ("turn", 360.0)];
+    units.iter().find_map(|(unit, factor)| {
+        let value: f32 = s.strip_suffix(unit)?.parse().ok()?;
+        Some(value * factor)
+    })
+}
+
+/// Parse a pixel or percentage length (`"10px"`, `"10"` or `"50%"`).
+fn parse_length_percent(s: &str) -> Option<PositionOffset> {
+    match s.strip_suffix('%') {
+        Some(pct) => pct.parse().ok().map(PositionOffset::Percent),
+        None => s.strip_suffix("px").unwrap_or(s).parse().ok().map(PositionOffset::Px),
+    }
+}
+
 // ============================================================================
 // Color type
 // ============================================================================
 
-/// Color value - supports hex (#RGB, #RRGGBB, #RRGGBBAA) and rgb/rgba functions.
-#[derive(Debug, Clone, Default)]
+/// Color value - supports hex, named colors, the CSS Color 4 functions
+/// (`rgb()`, `hsl()`, `hwb()`, `lab()`, `lch()`, `oklab()`, `oklch()`,
+/// `color()`) and `currentColor`.
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
     pub b: u8,
     pub a: u8,
+    /// `currentColor`: replaced by the inherited text color during layout
+    pub current: bool,
 }
 
 impl Color {
-    pub const TRANSPARENT: Self = Self { r: 0, g: 0, b: 0, a: 0 };
-    pub const BLACK: Self = Self { r: 0, g: 0, b: 0, a: 255 };
-    pub const WHITE: Self = Self { r: 255, g: 255, b: 255, a: 255 };
+    pub const TRANSPARENT: Self = Self::rgba(0, 0, 0, 0);
+    pub const BLACK: Self = Self::rgba(0, 0, 0, 255);
+    pub const WHITE: Self = Self::rgba(255, 255, 255, 255);
+    /// `currentColor`, black until resolved.
+    pub const CURRENT: Self = Self { current: true, ..Self::BLACK };
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a, current: false }
+    }
 
     /// Convert to CSS color string for SVG output.
     pub fn to_css(&self) -> String {
@@ -169,165 +704,943 @@ impl Color {
         }
     }
 
+    /// Replace `currentColor` with `current`.
+    pub fn resolve(&self, current: &Color) -> Color {
+        if self.current { current.clone() } else { self.clone() }
+    }
+
     /// Parse a color string.
     pub fn parse(s: &str) -> Option<Self> {
-        let s = s.trim();
+        if s.trim().eq_ignore_ascii_case("currentcolor") {
+            return Some(Self::CURRENT);
+        }
+        color::parse(s)
+    }
+}
 
-        // Hex colors
-        if s.starts_with('#') {
-            let hex = &s[1..];
-            return match hex.len() {
-                3 => {
-                    // #RGB
-                    let r = u8::from_str_radix(&hex[0..1], 16).ok()? * 17;
-                    let g = u8::from_str_radix(&hex[1..2], 16).ok()? * 17;
-                    let b = u8::from_str_radix(&hex[2..3], 16).ok()? * 17;
-                    Some(Self { r, g, b, a: 255 })
-                }
-                6 => {
-                    // #RRGGBB
-                    let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
-                    let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
-                    let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
-                    Some(Self { r, g, b, a: 255 })
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Color::parse(&s).ok_or_else(|| serde::de::Error::custom(format!("invalid color: {}", s)))
+    }
+}
+
+// ============================================================================
+// Border types
+// ============================================================================
+
+/// A single border side: a width in pixels or a `"<width> <style> <color>"`
+/// shorthand (e.g. `"1px dashed #ccc"`). Unset parts fall back to
+/// `borderWidth`, `borderStyle` and `borderColor`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Border {
+    pub width: Option<f32>,
+    pub style: Option<BorderStyle>,
+    pub color: Option<Color>,
+}
+
+impl Border {
+    /// Parse a CSS border shorthand. `none` yields a zero-width border.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut border = Border::default();
+        for token in split_top_level(s.trim(), char::is_whitespace) {
+            if token == "none" && border.width.is_none() {
+                border.width = Some(0.0);
+            } else if let Ok(width) = token.strip_suffix("px").unwrap_or(token).parse::<f32>() {
+                if border.width.replace(width).is_some() || width < 0.0 {
+                    return None;
                 }
-                8 => {
-                    // #RRGGBBAA
-                    let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
-                    let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
-                    let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
-                    let a = u8::from_str_radix(&hex[6..8], 16).ok()?;
-                    Some(Self { r, g, b, a })
+            } else if let Some(style) = BorderStyle::parse(token) {
+                if border.style.replace(style).is_some() {
+                    return None;
                 }
-                _ => None,
-            };
+            } else if border.color.replace(Color::parse(token)?).is_some() {
+                return None;
+            }
         }
+        Some(border)
+    }
+}
 
-        // rgb(r, g, b) or rgba(r, g, b, a)
-        if s.starts_with("rgb") {
-            let inner = s
-                .trim_start_matches("rgba")
-                .trim_start_matches("rgb")
-                .trim_start_matches('(')
-                .trim_end_matches(')');
-            let parts: Vec<&str> = inner.split(',').map(|p| p.trim()).collect();
-
-            if parts.len() >= 3 {
-                let r = parts[0].parse().ok()?;
-                let g = parts[1].parse().ok()?;
-                let b = parts[2].parse().ok()?;
-                let a = if parts.len() >= 4 {
-                    let a_float: f32 = parts[3].parse().ok()?;
-                    (a_float * 255.0) as u8
-                } else {
-                    255
-                };
-                return Some(Self { r, g, b, a });
+impl<'de> Deserialize<'de> for Border {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Width(f32),
+            Shorthand(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Width(width) => Ok(Border {
+                width: Some(width),
+                ..Default::default()
+            }),
+            Raw::Shorthand(s) => {
+                Border::parse(&s).ok_or_else(|| serde::de::Error::custom(format!("invalid border: {}", s)))
             }
         }
+    }
+}
+
+// ============================================================================
+// Shadow types
+// ============================================================================
+
+/// A comma-separated list of box shadows (e.g. `"0 4px 12px rgba(0,0,0,0.2), inset 0 0 0 1px #fff"`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoxShadow(pub Vec<Shadow>);
+
+/// A single box shadow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shadow {
+    pub offset_x: f32,
+    pub offset_y: f32,
+    pub blur: f32,
+    pub spread: f32,
+    pub color: Color,
+    pub inset: bool,
+}
+
+impl BoxShadow {
+    /// Parse a CSS `box-shadow` value. `none` yields an empty list.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if s == "none" {
+            return Some(BoxShadow(Vec::new()));
+        }
+        split_top_level(s, |c| c == ',')
+            .into_iter()
+            .map(Shadow::parse)
+            .collect::<Option<_>>()
+            .map(BoxShadow)
+    }
+}
+
+impl Shadow {
+    /// Parse a single shadow: `[inset] <x> <y> [blur] [spread] [color]`,
+    /// with the color and `inset` allowed at either end.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut lengths = Vec::new();
+        let mut color = None;
+        let mut inset = false;
+        // Lengths must be contiguous
+        let mut lengths_done = false;
+
+        for token in split_top_level(s, char::is_whitespace) {
+            if let Ok(length) = token.strip_suffix("px").unwrap_or(token).parse::<f32>() {
+                if lengths_done {
+                    return None;
+                }
+                lengths.push(length);
+                continue;
+            }
+
+            if token == "inset" && !inset {
+                inset = true;
+            } else if color.is_none() {
+                color = Some(Color::parse(token)?);
+            } else {
+                return None;
+            }
+            lengths_done = !lengths.is_empty();
+        }
+
+        let (offset_x, offset_y, blur, spread) = match lengths[..] {
+            [x, y] => (x, y, 0.0, 0.0),
+            [x, y, blur] => (x, y, blur, 0.0),
+            [x, y, blur, spread] => (x, y, blur, spread),
+            _ => return None,
+        };
+        if blur < 0.0 {
+            return None;
+        }
+
+        Some(Shadow {
+            offset_x,
+            offset_y,
+            blur,
+            spread,
+            color: color.unwrap_or(Color::CURRENT),
+            inset,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for BoxShadow {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        BoxShadow::parse(&s).ok_or_else(|| serde::de::Error::custom(format!("invalid box shadow: {}", s)))
+    }
+}
+
+// ============================================================================
+// Transform types
+// ============================================================================
+
+/// A CSS transform function list (e.g. `"rotate(12deg) scale(1.1)"`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transform(pub Vec<TransformFunction>);
+
+/// A single 2D transform function. Angles are in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransformFunction {
+    /// Percentages are relative to the element's size
+    Translate(PositionOffset, PositionOffset),
+    Scale(f32, f32),
+    Rotate(f32),
+    Skew(f32, f32),
+    Matrix([f32; 6]),
+}
+
+impl Transform {
+    /// Parse a whitespace-separated list of transform functions.
+    /// `none` yields an empty list.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if s == "none" {
+            return Some(Transform(Vec::new()));
+        }
+        split_top_level(s, char::is_whitespace)
+            .into_iter()
+            .map(TransformFunction::parse)
+            .collect::<Option<_>>()
+            .map(Transform)
+    }
+
+    /// Compose the functions into an SVG matrix `[a, b, c, d, e, f]` for
+    /// an element of the given size, relative to the transform origin.
+    pub fn to_matrix(&self, width: f32, height: f32) -> [f32; 6] {
+        self.0.iter().fold(IDENTITY_MATRIX, |matrix, function| {
+            multiply_matrix(matrix, function.to_matrix(width, height))
+        })
+    }
+}
+
+/// The identity transform matrix.
+pub const IDENTITY_MATRIX: [f32; 6] = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+/// Multiply two SVG matrices, so that `b` is applied first.
+pub fn multiply_matrix(a: [f32; 6], b: [f32; 6]) -> [f32; 6] {
+    [
+        a[0] * b[0] + a[2] * b[1],
+        a[1] * b[0] + a[3] * b[1],
+        a[0] * b[2] + a[2] * b[3],
+        a[1] * b[2] + a[3] * b[3],
+        a[0] * b[4] + a[2] * b[5] + a[4],
+        a[1] * b[4] + a[3] * b[5] + a[5],
+    ]
+}
+
+impl TransformFunction {
+    /// Parse a single function like `translate(10px, 50%)` or `skewX(10deg)`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let name = s.split('(').next()?.trim();
+        let args = function_args(s, name)?;
+        let args = split_top_level(args, |c| c == ',');
+        let numbers = || args.iter().map(|a| a.parse::<f32>().ok()).collect::<Option<Vec<_>>>();
+        let angles = || args.iter().map(|a| parse_angle(a)).collect::<Option<Vec<_>>>();
+        let lengths = || args.iter().map(|a| parse_length_percent(a)).collect::<Option<Vec<_>>>();
+        let zero = PositionOffset::Px(0.0);
+
+        match (name, args.len()) {
+            ("translate", 1 | 2) => {
+                let lengths = lengths()?;
+                Some(TransformFunction::Translate(lengths[0], lengths.get(1).copied().unwrap_or(zero)))
+            }
+            ("translateX", 1) => Some(TransformFunction::Translate(lengths()?[0], zero)),
+            ("translateY", 1) => Some(TransformFunction::Translate(zero, lengths()?[0])),
+            ("scale", 1 | 2) => {
+                let n = numbers()?;
+                Some(TransformFunction::Scale(n[0], n.get(1).copied().unwrap_or(n[0])))
+            }
+            ("scaleX", 1) => Some(TransformFunction::Scale(numbers()?[0], 1.0)),
+            ("scaleY", 1) => Some(TransformFunction::Scale(1.0, numbers()?[0])),
+            ("rotate", 1) => Some(TransformFunction::Rotate(angles()?[0])),
+            ("skew", 1 | 2) => {
+                let a = angles()?;
+                Some(TransformFunction::Skew(a[0], a.get(1).copied().unwrap_or(0.0)))
+            }
+            ("skewX", 1) => Some(TransformFunction::Skew(angles()?[0], 0.0)),
+            ("skewY", 1) => Some(TransformFunction::Skew(0.0, angles()?[0])),
+            ("matrix", 6) => numbers()?.try_into().ok().map(TransformFunction::Matrix),
+            _ => None,
+        }
+    }
+
+    fn to_matrix(self, width: f32, height: f32) -> [f32; 6] {
+        match self {
+            TransformFunction::Translate(x, y) => [1.0, 0.0, 0.0, 1.0, x.resolve(width), y.resolve(height)],
+            TransformFunction::Scale(x, y) => [x, 0.0, 0.0, y, 0.0, 0.0],
+            TransformFunction::Rotate(deg) => {
+                let (sin, cos) = deg.to_radians().sin_cos();
+                [cos, sin, -sin, cos, 0.0, 0.0]
+            }
+            TransformFunction::Skew(x, y) => [1.0, y.to_radians().tan(), x.to_radians().tan(), 1.0, 0.0, 0.0],
+            TransformFunction::Matrix(m) => m,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Transform {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Transform::parse(&s).ok_or_else(|| serde::de::Error::custom(format!("invalid transform: {}", s)))
+    }
+}
+
+// ============================================================================
+// Gradient types
+// ============================================================================
+
+/// A `linear-gradient()` or `radial-gradient()` background image.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Gradient {
+    Linear {
+        direction: LinearDirection,
+        stops: Vec<ColorStop>,
+    },
+    Radial {
+        shape: RadialShape,
+        size: RadialSize,
+        center: ObjectPosition,
+        stops: Vec<ColorStop>,
+    },
+}
+
+/// Direction of a linear gradient.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LinearDirection {
+    /// CSS angle in degrees (0 = to top, 90 = to right)
+    Angle(f32),
+    /// `to <corner>`; the angle depends on the box's aspect ratio.
+    /// Components are -1 (left/top) or 1 (right/bottom).
+    Corner { x: f32, y: f32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RadialShape {
+    Circle,
+    Ellipse,
+}
+
+/// Ending shape size of a radial gradient.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RadialSize {
+    ClosestSide,
+    ClosestCorner,
+    FarthestSide,
+    FarthestCorner,
+    /// Explicit circle radius in pixels
+    Px(f32),
+}
+
+/// A gradient color stop with an optional position along the gradient line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorStop {
+    pub color: Color,
+    pub position: Option<PositionOffset>,
+}
+
+impl Gradient {
+    /// Parse a CSS gradient function.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if let Some(args) = function_args(s, "linear-gradient") {
+            let parts = split_top_level(args, |c| c == ',');
+            let (direction, stops) = match parts.first().and_then(|p| parse_linear_direction(p)) {
+                Some(direction) => (direction, &parts[1..]),
+                None => (LinearDirection::Angle(180.0), &parts[..]),
+            };
+            return Some(Gradient::Linear {
+                direction,
+                stops: parse_color_stops(stops)?,
+            });
+        }
+
+        if let Some(args) = function_args(s, "radial-gradient") {
+            let parts = split_top_level(args, |c| c == ',');
+            let first = *parts.first()?;
+            let is_config = first.starts_with("at ")
+                || first.split_whitespace().any(|t| {
+                    matches!(
+                        t,
+                        "circle"
+                            | "ellipse"
+                            | "closest-side"
+                            | "closest-corner"
+                            | "farthest-side"
+                            | "farthest-corner"
+                    )
+                });
+            let (mut shape, mut size, mut center) = (None, None, ObjectPosition::default());
+            if is_config {
+                let (shape_size, at) = match first.split_once("at ") {
+                    Some((before, after)) => (before, Some(after)),
+                    None => (first, None),
+                };
+                for token in shape_size.split_whitespace() {
+                    match token {
+                        "circle" => shape = Some(RadialShape::Circle),
+                        "ellipse" => shape = Some(RadialShape::Ellipse),
+                        "closest-side" => size = Some(RadialSize::ClosestSide),
+                        "closest-corner" => size = Some(RadialSize::ClosestCorner),
+                        "farthest-side" => size = Some(RadialSize::FarthestSide),
+                        "farthest-corner" => size = Some(RadialSize::FarthestCorner),
+                        t => size = Some(RadialSize::Px(t.strip_suffix("px")?.parse().ok()?)),
+                    }
+                }
+                if let Some(at) = at {
+                    center = ObjectPosition::parse(at)?;
+                }
+            }
+            // An explicit radius implies a circle
+            let shape = match (shape, size) {
+                (Some(RadialShape::Ellipse), Some(RadialSize::Px(_))) => return None,
+                (Some(shape), _) => shape,
+                (None, Some(RadialSize::Px(_))) => RadialShape::Circle,
+                (None, _) => RadialShape::Ellipse,
+            };
+            let stops = if is_config { &parts[1..] } else { &parts[..] };
+            return Some(Gradient::Radial {
+                shape,
+                size: size.unwrap_or(RadialSize::FarthestCorner),
+                center,
+                stops: parse_color_stops(stops)?,
+            });
+        }
+
+        None
+    }
+}
+
+fn parse_linear_direction(s: &str) -> Option<LinearDirection> {
+    if let Some(sides) = s.strip_prefix("to ") {
+        let (mut x, mut y) = (0.0, 0.0);
+        for side in sides.split_whitespace() {
+            match side {
+                "left" => x = -1.0,
+                "right" => x = 1.0,
+                "top" => y = -1.0,
+                "bottom" => y = 1.0,
+                _ => return None,
+            }
+        }
+        return match (x, y) {
+            (0.0, 0.0) => None,
+            (x, 0.0) => Some(LinearDirection::Angle(if x > 0.0 { 90.0 } else { 270.0 })),
+            (0.0, y) => Some(LinearDirection::Angle(if y > 0.0 { 180.0 } else { 0.0 })),
+            (x, y) => Some(LinearDirection::Corner { x, y }),
+        };
+    }
+
+    parse_angle(s).map(LinearDirection::Angle)
+}
+
+fn parse_color_stops(parts: &[&str]) -> Option<Vec<ColorStop>> {
+    let mut stops = Vec::new();
+    for part in parts {
+        let tokens = split_top_level(part, char::is_whitespace);
+        let color = Color::parse(tokens.first()?)?;
+        let mut positions = tokens[1..].iter().map(|t| parse_length_percent(t));
+        match (positions.next(), positions.next(), positions.next()) {
+            (None, _, _) => stops.push(ColorStop { color, position: None }),
+            // Two positions create a hard color band
+            (Some(a), b, None) => {
+                stops.push(ColorStop { color: color.clone(), position: Some(a?) });
+                if let Some(b) = b {
+                    stops.push(ColorStop { color, position: Some(b?) });
+                }
+            }
+            _ => return None,
+        }
+    }
+    (stops.len() >= 2).then_some(stops)
+}
+
+impl<'de> Deserialize<'de> for Gradient {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Gradient::parse(&s).ok_or_else(|| serde::de::Error::custom(format!("invalid gradient: {}", s)))
+    }
+}
+
+// ============================================================================
+// Enum types for flex properties
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Display {
+    #[default]
+    Block,
+    Flex,
+    None,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BorderStyle {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+    Double,
+}
+
+impl BorderStyle {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "solid" => Some(BorderStyle::Solid),
+            "dashed" => Some(BorderStyle::Dashed),
+            "dotted" => Some(BorderStyle::Dotted),
+            "double" => Some(BorderStyle::Double),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Overflow {
+    #[default]
+    Visible,
+    Hidden,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Position {
+    #[default]
+    Static,
+    Relative,
+    Absolute,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FlexDirection {
+    #[default]
+    Row,
+    Column,
+    RowReverse,
+    ColumnReverse,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum JustifyContent {
+    #[default]
+    FlexStart,
+    FlexEnd,
+    Center,
+    SpaceBetween,
+    SpaceAround,
+    SpaceEvenly,
+    /// Behaves as `flex-start` in flex layout
+    Stretch,
+}
+
+/// How wrapped flex lines are distributed along the cross axis.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AlignContent {
+    /// Same as `stretch`
+    #[default]
+    Normal,
+    Start,
+    End,
+    FlexStart,
+    FlexEnd,
+    Center,
+    Stretch,
+    SpaceBetween,
+    SpaceAround,
+    SpaceEvenly,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AlignItems {
+    FlexStart,
+    FlexEnd,
+    Center,
+    #[default]
+    Stretch,
+    Baseline,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FlexWrap {
+    #[default]
+    Nowrap,
+    Wrap,
+    WrapReverse,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub enum GridAutoFlow {
+    #[default]
+    #[serde(rename = "row")]
+    Row,
+    #[serde(rename = "column")]
+    Column,
+    #[serde(rename = "row dense", alias = "dense")]
+    RowDense,
+    #[serde(rename = "column dense")]
+    ColumnDense,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextAlign {
+    /// Left for left-to-right text, right for right-to-left text
+    #[default]
+    Start,
+    /// Right for left-to-right text, left for right-to-left text
+    End,
+    Left,
+    Center,
+    Right,
+    Justify,
+}
+
+/// Base direction of text and of flex rows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextOverflow {
+    Clip,
+    Ellipsis,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WhiteSpace {
+    #[default]
+    Normal,
+    Nowrap,
+}
+
+/// Lines drawn by `textDecorationLine` (e.g. `"underline line-through"`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TextDecorationLine {
+    pub underline: bool,
+    pub overline: bool,
+    pub line_through: bool,
+}
+
+impl TextDecorationLine {
+    /// Parse `"none"` or a space-separated list of `underline`, `overline`
+    /// and `line-through`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut line = TextDecorationLine::default();
+        if s.trim() == "none" {
+            return Some(line);
+        }
+        for part in s.split_whitespace() {
+            let flag = match part {
+                "underline" => &mut line.underline,
+                "overline" => &mut line.overline,
+                "line-through" => &mut line.line_through,
+                _ => return None,
+            };
+            if *flag {
+                return None;
+            }
+            *flag = true;
+        }
+        line.is_some().then_some(line)
+    }
+
+    /// Whether any line is drawn.
+    pub fn is_some(&self) -> bool {
+        self.underline || self.overline || self.line_through
+    }
+}
+
+impl<'de> Deserialize<'de> for TextDecorationLine {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        TextDecorationLine::parse(&s)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid text decoration line: {}", s)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextDecorationStyle {
+    #[default]
+    Solid,
+    Double,
+    Dotted,
+    Dashed,
+    Wavy,
+}
+
+/// How the font size of a text element is chosen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FontSizeMode {
+    /// Use `fontSize` as is
+    #[default]
+    Fixed,
+    /// Use the largest size between `minFontSize` and `maxFontSize` that fits the box
+    Fit,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextRendering {
+    #[default]
+    Text,
+    Vector,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ObjectFit {
+    #[default]
+    Contain,
+    Cover,
+    Fill,
+}
+
+/// Font slope: `"normal"`, `"italic"` or `"oblique [<angle>]"`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum FontStyle {
+    #[default]
+    Normal,
+    Italic,
+    /// Slant angle in degrees
+    Oblique(f32),
+}
+
+impl FontStyle {
+    /// Slant of a bare `oblique`, in degrees.
+    pub const DEFAULT_OBLIQUE_ANGLE: f32 = 14.0;
+
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        match s {
+            "normal" => Some(FontStyle::Normal),
+            "italic" => Some(FontStyle::Italic),
+            "oblique" => Some(FontStyle::Oblique(Self::DEFAULT_OBLIQUE_ANGLE)),
+            _ => {
+                let angle = parse_angle(s.strip_prefix("oblique ")?.trim())?;
+                (-90.0..=90.0).contains(&angle).then_some(FontStyle::Oblique(angle))
+            }
+        }
+    }
+
+    /// CSS value, e.g. `"oblique 10deg"`.
+    pub fn to_css(&self) -> String {
+        match self {
+            FontStyle::Normal => "normal".to_string(),
+            FontStyle::Italic => "italic".to_string(),
+            FontStyle::Oblique(angle) => format!("oblique {}deg", angle),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for FontStyle {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        FontStyle::parse(&s).ok_or_else(|| serde::de::Error::custom(format!("invalid font style: {}", s)))
+    }
+}
+
+impl Serialize for FontStyle {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_css())
+    }
+}
+
+/// Font width as a percentage of normal: a keyword such as `"condensed"`,
+/// a percentage string (`"75%"`) or a number.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FontStretch(pub f32);
+
+impl Default for FontStretch {
+    fn default() -> Self {
+        FontStretch::NORMAL
+    }
+}
+
+impl FontStretch {
+    pub const NORMAL: FontStretch = FontStretch(100.0);
+
+    const KEYWORDS: [(&'static str, f32); 9] = [
+        ("ultra-condensed", 50.0),
+        ("extra-condensed", 62.5),
+        ("condensed", 75.0),
+        ("semi-condensed", 87.5),
+        ("normal", 100.0),
+        ("semi-expanded", 112.5),
+        ("expanded", 125.0),
+        ("extra-expanded", 150.0),
+        ("ultra-expanded", 200.0),
+    ];
+
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if let Some((_, pct)) = Self::KEYWORDS.iter().find(|(name, _)| *name == s) {
+            return Some(FontStretch(*pct));
+        }
+        let pct: f32 = s.strip_suffix('%')?.trim().parse().ok()?;
+        (pct > 0.0).then_some(FontStretch(pct))
+    }
+
+    /// CSS value: the matching keyword, otherwise a percentage.
+    pub fn to_css(&self) -> String {
+        match Self::KEYWORDS.iter().find(|(_, pct)| *pct == self.0) {
+            Some((name, _)) => name.to_string(),
+            None => format!("{}%", self.0),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for FontStretch {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(f32),
+            String(String),
+        }
 
-        // Named colors (basic support)
-        match s.to_lowercase().as_str() {
-            "transparent" => Some(Self::TRANSPARENT),
-            "black" => Some(Self::BLACK),
-            "white" => Some(Self::WHITE),
-            "red" => Some(Self { r: 255, g: 0, b: 0, a: 255 }),
-            "green" => Some(Self { r: 0, g: 128, b: 0, a: 255 }),
-            "blue" => Some(Self { r: 0, g: 0, b: 255, a: 255 }),
-            _ => None,
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(pct) if pct > 0.0 => Ok(FontStretch(pct)),
+            Raw::Number(pct) => Err(serde::de::Error::custom(format!("invalid font stretch: {}", pct))),
+            Raw::String(s) => FontStretch::parse(&s)
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid font stretch: {}", s))),
         }
     }
 }
 
-impl<'de> Deserialize<'de> for Color {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+impl Serialize for FontStretch {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
-        D: serde::Deserializer<'de>,
+        S: serde::Serializer,
     {
-        let s = String::deserialize(deserializer)?;
-        Color::parse(&s).ok_or_else(|| serde::de::Error::custom(format!("invalid color: {}", s)))
+        serializer.serialize_str(&self.to_css())
     }
 }
 
-// ============================================================================
-// Enum types for flex properties
-// ============================================================================
-
-#[derive(Debug, Clone, Copy, Default, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum Display {
-    #[default]
-    Block,
-    Flex,
-    None,
+/// Image alignment within its box, e.g. `"center"`, `"left top"`, `"25% 75%"`
+/// or `"10px 20px"`. Defaults to centered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObjectPosition {
+    pub x: PositionOffset,
+    pub y: PositionOffset,
 }
 
-#[derive(Debug, Clone, Copy, Default, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum FlexDirection {
-    #[default]
-    Row,
-    Column,
-    RowReverse,
-    ColumnReverse,
+/// One axis of an `ObjectPosition`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PositionOffset {
+    /// Percentage of the free space (0 = start, 100 = end)
+    Percent(f32),
+    /// Offset in pixels from the start edge
+    Px(f32),
 }
 
-#[derive(Debug, Clone, Copy, Default, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum JustifyContent {
-    #[default]
-    FlexStart,
-    FlexEnd,
-    Center,
-    SpaceBetween,
-    SpaceAround,
-    SpaceEvenly,
+impl PositionOffset {
+    /// Resolve against a length in pixels (percentages are of `length`).
+    pub fn resolve(self, length: f32) -> f32 {
+        match self {
+            PositionOffset::Percent(pct) => length * pct / 100.0,
+            PositionOffset::Px(px) => px,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, Default, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum AlignItems {
-    FlexStart,
-    FlexEnd,
-    Center,
-    #[default]
-    Stretch,
-    Baseline,
+impl Default for ObjectPosition {
+    fn default() -> Self {
+        Self {
+            x: PositionOffset::Percent(50.0),
+            y: PositionOffset::Percent(50.0),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, Default, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum FlexWrap {
-    #[default]
-    Nowrap,
-    Wrap,
-}
+impl ObjectPosition {
+    /// Parse a CSS `object-position` value (one or two components).
+    pub fn parse(s: &str) -> Option<Self> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        let (first, second) = match parts.as_slice() {
+            [one] => (*one, "center"),
+            [one, two] => (*one, *two),
+            _ => return None,
+        };
 
-#[derive(Debug, Clone, Copy, Default, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum TextAlign {
-    #[default]
-    Left,
-    Center,
-    Right,
-    Justify,
-}
+        // Vertical keywords first ("top left") are swapped into x/y order
+        let (x, y) = if matches!(first, "top" | "bottom") || matches!(second, "left" | "right") {
+            (second, first)
+        } else {
+            (first, second)
+        };
 
-#[derive(Debug, Clone, Copy, Default, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum TextRendering {
-    #[default]
-    Text,
-    Vector,
+        let component = |v: &str, start: &str, end: &str| match v {
+            "center" => Some(PositionOffset::Percent(50.0)),
+            v if v == start => Some(PositionOffset::Percent(0.0)),
+            v if v == end => Some(PositionOffset::Percent(100.0)),
+            v => match v.strip_suffix('%') {
+                Some(pct) => pct.parse().ok().map(PositionOffset::Percent),
+                None => v.strip_suffix("px").unwrap_or(v).parse().ok().map(PositionOffset::Px),
+            },
+        };
+
+        Some(Self {
+            x: component(x, "left", "right")?,
+            y: component(y, "top", "bottom")?,
+        })
+    }
 }
 
-#[derive(Debug, Clone, Copy, Default, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum ObjectFit {
-    #[default]
-    Contain,
-    Cover,
-    Fill,
+impl<'de> Deserialize<'de> for ObjectPosition {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        ObjectPosition::parse(&s)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid object position: {}", s)))
+    }
 }
 
 /// Font weight - can be a number (100-900) or keyword.
@@ -370,6 +1683,31 @@ impl<'de> Deserialize<'de> for FontWeight {
 // Style structs
 // ============================================================================
 
+/// Text properties a container passes down to the text inside it.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InheritableTextStyle {
+    pub direction: Option<Direction>,
+    pub color: Option<Color>,
+    pub font_family: Option<String>,
+    pub font_size: Option<f32>,
+    pub font_weight: Option<FontWeight>,
+    pub line_height: Option<f32>,
+    pub text_align: Option<TextAlign>,
+    pub letter_spacing: Option<f32>,
+}
+
+/// Properties of an element as a flex item, shared by every element type.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FlexItemStyle {
+    pub flex_grow: Option<f32>,
+    pub flex_shrink: Option<f32>,
+    pub flex_basis: Option<DimensionOrAuto>,
+    pub align_self: Option<AlignItems>,
+    pub order: Option<i32>,
+}
+
 /// Style for Box elements (block container).
 #[derive(Debug, Clone, Default, Deserialize)]
 #[serde(rename_all = "camelCase", default)]
@@ -377,6 +1715,10 @@ pub struct BoxStyle {
     // Display
     pub display: Option<Display>,
 
+    // Inherited text properties (cascade to descendant text)
+    #[serde(flatten)]
+    pub text: InheritableTextStyle,
+
     // Dimensions
     pub width: Option<Dimension>,
     pub height: Option<Dimension>,
@@ -386,15 +1728,43 @@ pub struct BoxStyle {
     pub max_height: Option<Dimension>,
 
     // Spacing
-    pub margin: Option<Spacing>,
+    pub margin: Option<Margin>,
     pub padding: Option<Spacing>,
 
     // Visual
     pub background_color: Option<Color>,
+    pub background_image: Option<Gradient>,
+    pub box_shadow: Option<BoxShadow>,
     pub border_width: Option<f32>,
     pub border_color: Option<Color>,
+    pub border_style: Option<BorderStyle>,
+    pub border_top: Option<Border>,
+    pub border_right: Option<Border>,
+    pub border_bottom: Option<Border>,
+    pub border_left: Option<Border>,
     pub border_radius: Option<BorderRadius>,
     pub opacity: Option<f32>,
+    pub overflow: Option<Overflow>,
+
+    // Flex child properties
+    #[serde(flatten)]
+    pub flex_item: FlexItemStyle,
+
+    // Positioning
+    pub position: Option<Position>,
+    pub top: Option<Dimension>,
+    pub right: Option<Dimension>,
+    pub bottom: Option<Dimension>,
+    pub left: Option<Dimension>,
+    pub z_index: Option<i32>,
+
+    // Transform
+    pub transform: Option<Transform>,
+    pub transform_origin: Option<ObjectPosition>,
+
+    // Grid child properties
+    pub grid_column: Option<GridLine>,
+    pub grid_row: Option<GridLine>,
 }
 
 /// Style for Flex elements (flex container).
@@ -404,6 +1774,10 @@ pub struct FlexStyle {
     // Display
     pub display: Option<Display>,
 
+    // Inherited text properties (cascade to descendant text)
+    #[serde(flatten)]
+    pub text: InheritableTextStyle,
+
     // Dimensions
     pub width: Option<Dimension>,
     pub height: Option<Dimension>,
@@ -413,22 +1787,120 @@ pub struct FlexStyle {
     pub max_height: Option<Dimension>,
 
     // Spacing
-    pub margin: Option<Spacing>,
+    pub margin: Option<Margin>,
     pub padding: Option<Spacing>,
 
     // Flex container
     pub flex_direction: Option<FlexDirection>,
     pub justify_content: Option<JustifyContent>,
     pub align_items: Option<AlignItems>,
-    pub gap: Option<f32>,
+    pub align_content: Option<AlignContent>,
+    pub gap: Option<Dimension>,
+    pub row_gap: Option<Dimension>,
+    pub column_gap: Option<Dimension>,
     pub flex_wrap: Option<FlexWrap>,
 
     // Visual
     pub background_color: Option<Color>,
+    pub background_image: Option<Gradient>,
+    pub box_shadow: Option<BoxShadow>,
+    pub border_width: Option<f32>,
+    pub border_color: Option<Color>,
+    pub border_style: Option<BorderStyle>,
+    pub border_top: Option<Border>,
+    pub border_right: Option<Border>,
+    pub border_bottom: Option<Border>,
+    pub border_left: Option<Border>,
+    pub border_radius: Option<BorderRadius>,
+    pub opacity: Option<f32>,
+    pub overflow: Option<Overflow>,
+
+    // Flex child properties
+    #[serde(flatten)]
+    pub flex_item: FlexItemStyle,
+
+    // Positioning
+    pub position: Option<Position>,
+    pub top: Option<Dimension>,
+    pub right: Option<Dimension>,
+    pub bottom: Option<Dimension>,
+    pub left: Option<Dimension>,
+    pub z_index: Option<i32>,
+
+    // Transform
+    pub transform: Option<Transform>,
+    pub transform_origin: Option<ObjectPosition>,
+
+    // Grid child properties
+    pub grid_column: Option<GridLine>,
+    pub grid_row: Option<GridLine>,
+}
+
+/// Style for Grid elements (grid container).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GridStyle {
+    // Display
+    pub display: Option<Display>,
+
+    // Inherited text properties (cascade to descendant text)
+    #[serde(flatten)]
+    pub text: InheritableTextStyle,
+
+    // Dimensions
+    pub width: Option<Dimension>,
+    pub height: Option<Dimension>,
+    pub min_width: Option<Dimension>,
+    pub max_width: Option<Dimension>,
+    pub min_height: Option<Dimension>,
+    pub max_height: Option<Dimension>,
+
+    // Spacing
+    pub margin: Option<Margin>,
+    pub padding: Option<Spacing>,
+
+    // Grid container
+    pub grid_template_columns: Option<GridTemplate>,
+    pub grid_template_rows: Option<GridTemplate>,
+    pub grid_auto_flow: Option<GridAutoFlow>,
+    pub gap: Option<Dimension>,
+    pub row_gap: Option<Dimension>,
+    pub column_gap: Option<Dimension>,
+
+    // Visual
+    pub background_color: Option<Color>,
+    pub background_image: Option<Gradient>,
+    pub box_shadow: Option<BoxShadow>,
     pub border_width: Option<f32>,
     pub border_color: Option<Color>,
+    pub border_style: Option<BorderStyle>,
+    pub border_top: Option<Border>,
+    pub border_right: Option<Border>,
+    pub border_bottom: Option<Border>,
+    pub border_left: Option<Border>,
     pub border_radius: Option<BorderRadius>,
     pub opacity: Option<f32>,
+    pub overflow: Option<Overflow>,
+
+    // Flex child properties
+    #[serde(flatten)]
+    pub flex_item: FlexItemStyle,
+
+    // Positioning
+    pub position: Option<Position>,
+    pub top: Option<Dimension>,
+    pub right: Option<Dimension>,
+    pub bottom: Option<Dimension>,
+    pub left: Option<Dimension>,
+    pub z_index: Option<i32>,
+
+    // Transform
+    pub transform: Option<Transform>,
+    pub transform_origin: Option<ObjectPosition>,
+
+    // Grid child properties
+    pub grid_column: Option<GridLine>,
+    pub grid_row: Option<GridLine>,
 }
 
 /// Style for Text elements.
@@ -439,15 +1911,66 @@ pub struct TextStyle {
     pub font_family: Option<String>,
     pub font_size: Option<f32>,
     pub font_weight: Option<FontWeight>,
+    pub font_style: Option<FontStyle>,
+    pub font_stretch: Option<FontStretch>,
     pub line_height: Option<f32>,
     pub text_align: Option<TextAlign>,
+    pub direction: Option<Direction>,
     pub color: Option<Color>,
     pub letter_spacing: Option<f32>,
+    pub word_spacing: Option<f32>,
     pub text_rendering: Option<TextRendering>,
 
+    // Decoration
+    pub text_decoration_line: Option<TextDecorationLine>,
+    pub text_decoration_color: Option<Color>,
+    pub text_decoration_thickness: Option<f32>,
+    pub text_decoration_style: Option<TextDecorationStyle>,
+
+    // Font size fitting
+    pub font_size_mode: Option<FontSizeMode>,
+    pub min_font_size: Option<f32>,
+    pub max_font_size: Option<f32>,
+
+    // Line limits
+    pub max_lines: Option<usize>,
+    pub text_overflow: Option<TextOverflow>,
+    pub white_space: Option<WhiteSpace>,
+
+    // Spacing
+    pub margin: Option<Margin>,
+
     // Flex child properties
-    pub flex_grow: Option<f32>,
-    pub flex_shrink: Option<f32>,
+    #[serde(flatten)]
+    pub flex_item: FlexItemStyle,
+
+    // Positioning
+    pub position: Option<Position>,
+    pub top: Option<Dimension>,
+    pub right: Option<Dimension>,
+    pub bottom: Option<Dimension>,
+    pub left: Option<Dimension>,
+    pub z_index: Option<i32>,
+
+    // Transform
+    pub transform: Option<Transform>,
+    pub transform_origin: Option<ObjectPosition>,
+
+    // Grid child properties
+    pub grid_column: Option<GridLine>,
+    pub grid_row: Option<GridLine>,
+}
+
+/// Style for inline text spans. Unset properties inherit from the text element.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SpanStyle {
+    pub font_family: Option<String>,
+    pub font_size: Option<f32>,
+    pub font_weight: Option<FontWeight>,
+    pub font_style: Option<FontStyle>,
+    pub font_stretch: Option<FontStretch>,
+    pub color: Option<Color>,
 }
 
 /// Style for Image elements.
@@ -463,18 +1986,36 @@ pub struct ImageStyle {
     pub max_height: Option<Dimension>,
 
     // Spacing
-    pub margin: Option<Spacing>,
+    pub margin: Option<Margin>,
 
     // Image-specific
     pub object_fit: Option<ObjectFit>,
+    pub object_position: Option<ObjectPosition>,
 
     // Visual
     pub border_radius: Option<BorderRadius>,
     pub opacity: Option<f32>,
+    pub box_shadow: Option<BoxShadow>,
 
     // Flex child properties
-    pub flex_grow: Option<f32>,
-    pub flex_shrink: Option<f32>,
+    #[serde(flatten)]
+    pub flex_item: FlexItemStyle,
+
+    // Positioning
+    pub position: Option<Position>,
+    pub top: Option<Dimension>,
+    pub right: Option<Dimension>,
+    pub bottom: Option<Dimension>,
+    pub left: Option<Dimension>,
+    pub z_index: Option<i32>,
+
+    // Transform
+    pub transform: Option<Transform>,
+    pub transform_origin: Option<ObjectPosition>,
+
+    // Grid child properties
+    pub grid_column: Option<GridLine>,
+    pub grid_row: Option<GridLine>,
 }
 
 #[cfg(test)]
@@ -505,24 +2046,269 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_text_spans() {
+        let json = r###"{
+            "type": "text",
+            "spans": [
+                { "content": "Hello " },
+                { "content": "World", "style": { "fontWeight": "bold", "fontStyle": "italic" } }
+            ]
+        }"###;
+
+        let element: Element = serde_json::from_str(json).unwrap();
+        match element {
+            Element::Text { content: TextContent::Spans(spans), .. } => {
+                assert_eq!(spans.len(), 2);
+                assert_eq!(spans[1].style.font_weight.unwrap().0, 700);
+                assert_eq!(spans[1].style.font_style, Some(FontStyle::Italic));
+            }
+            _ => panic!("Expected Text element with spans"),
+        }
+
+        // Exactly one of `content` and `spans` is required
+        let error = serde_json::from_str::<Element>(r#"{ "type": "text" }"#).unwrap_err();
+        assert!(error.to_string().contains("text needs `content` or `spans`"));
+        let both = r#"{ "type": "text", "content": "Hi", "spans": [{ "content": "Hi" }] }"#;
+        let error = serde_json::from_str::<Element>(both).unwrap_err();
+        assert!(error.to_string().contains("text has both `content` and `spans`"));
+    }
+
+    #[test]
+    fn test_parse_grid_template() {
+        let template = GridTemplate::parse("200px 1fr repeat(2, minmax(50px, 1fr)) 25%").unwrap();
+        assert_eq!(template.0.len(), 4);
+        assert_eq!(
+            template.0[1],
+            GridTrack::Single(TrackSize {
+                min: TrackBreadth::Auto,
+                max: TrackBreadth::Fr(1.0)
+            })
+        );
+        assert_eq!(
+            template.0[2],
+            GridTrack::Repeat(
+                RepeatCount::Count(2),
+                vec![TrackSize {
+                    min: TrackBreadth::Px(50.0),
+                    max: TrackBreadth::Fr(1.0)
+                }]
+            )
+        );
+        assert!(GridTemplate::parse("minmax(1fr, 100px)").is_none());
+        assert!(GridTemplate::parse("10 bogus").is_none());
+    }
+
+    #[test]
+    fn test_parse_grid_line() {
+        assert_eq!(
+            GridLine::parse("1 / span 2").unwrap(),
+            GridLine {
+                start: GridPlacement::Line(1),
+                end: GridPlacement::Span(2)
+            }
+        );
+        assert_eq!(GridLine::parse("span 3").unwrap().start, GridPlacement::Span(3));
+        assert!(GridLine::parse("0").is_none());
+    }
+
+    #[test]
+    fn test_parse_object_position() {
+        let pos = ObjectPosition::parse("top left").unwrap();
+        assert_eq!(pos.x, PositionOffset::Percent(0.0));
+        assert_eq!(pos.y, PositionOffset::Percent(0.0));
+
+        let pos = ObjectPosition::parse("right").unwrap();
+        assert_eq!(pos.x, PositionOffset::Percent(100.0));
+        assert_eq!(pos.y, PositionOffset::Percent(50.0));
+
+        let pos = ObjectPosition::parse("25% 10px").unwrap();
+        assert_eq!(pos.x, PositionOffset::Percent(25.0));
+        assert_eq!(pos.y, PositionOffset::Px(10.0));
+
+        assert!(ObjectPosition::parse("left right middle").is_none());
+    }
+
+    #[test]
+    fn test_parse_font_style_and_stretch() {
+        assert_eq!(FontStyle::parse("italic"), Some(FontStyle::Italic));
+        assert_eq!(FontStyle::parse("oblique"), Some(FontStyle::Oblique(14.0)));
+        assert_eq!(FontStyle::parse("oblique -10deg"), Some(FontStyle::Oblique(-10.0)));
+        assert!(FontStyle::parse("oblique 100deg").is_none());
+        assert_eq!(FontStyle::Oblique(10.0).to_css(), "oblique 10deg");
+
+        assert_eq!(FontStretch::parse("semi-condensed"), Some(FontStretch(87.5)));
+        assert_eq!(FontStretch::parse("80%"), Some(FontStretch(80.0)));
+        assert!(FontStretch::parse("wide").is_none());
+        assert_eq!(FontStretch(125.0).to_css(), "expanded");
+        assert_eq!(FontStretch(80.0).to_css(), "80%");
+    }
+
+    #[test]
+    fn test_parse_text_decoration_line() {
+        let line = TextDecorationLine::parse("underline line-through").unwrap();
+        assert!(line.underline && line.line_through && !line.overline);
+        assert!(!TextDecorationLine::parse("none").unwrap().is_some());
+        assert!(TextDecorationLine::parse("underline underline").is_none());
+        assert!(TextDecorationLine::parse("blink").is_none());
+        assert!(TextDecorationLine::parse("").is_none());
+    }
+
+    #[test]
+    fn test_parse_transform() {
+        let transform = Transform::parse("translate(10px, 50%) rotate(0.25turn) scaleX(2)").unwrap();
+        assert_eq!(
+            transform.0,
+            vec![
+                TransformFunction::Translate(PositionOffset::Px(10.0), PositionOffset::Percent(50.0)),
+                TransformFunction::Rotate(90.0),
+                TransformFunction::Scale(2.0, 1.0),
+            ]
+        );
+
+        // translate, then rotate by 90 degrees, then scale x
+        let [a, b, c, d, e, f] = transform.to_matrix(100.0, 40.0);
+        assert!(a.abs() < 1e-6 && (b - 2.0).abs() < 1e-6 && (c + 1.0).abs() < 1e-6 && d.abs() < 1e-6);
+        assert_eq!((e, f), (10.0, 20.0));
+
+        assert_eq!(Transform::parse("skew(10deg) matrix(1, 0, 0, 1, 5, 5)").unwrap().0.len(), 2);
+        assert!(Transform::parse("none").unwrap().0.is_empty());
+        assert!(Transform::parse("rotate(10)").is_none());
+        assert!(Transform::parse("perspective(100px)").is_none());
+        assert!(Transform::parse("matrix(1, 0, 0)").is_none());
+    }
+
+    #[test]
+    fn test_parse_border() {
+        let border = Border::parse("2px dashed #ff0000").unwrap();
+        assert_eq!(border.width, Some(2.0));
+        assert_eq!(border.style, Some(BorderStyle::Dashed));
+        assert_eq!(border.color.unwrap().r, 255);
+
+        // Any order, any subset
+        let border = Border::parse("rgb(0, 0, 255) double").unwrap();
+        assert_eq!((border.width, border.style), (None, Some(BorderStyle::Double)));
+        assert_eq!(Border::parse("none").unwrap().width, Some(0.0));
+
+        assert!(Border::parse("1px 2px").is_none());
+        assert!(Border::parse("1px wavy").is_none());
+
+        let style: BoxStyle =
+            serde_json::from_str(r#"{ "borderTop": 3, "borderBottom": "1 dotted", "borderStyle": "dashed" }"#).unwrap();
+        assert_eq!(style.border_top.unwrap().width, Some(3.0));
+        assert_eq!(style.border_bottom.unwrap().style, Some(BorderStyle::Dotted));
+        assert_eq!(style.border_style, Some(BorderStyle::Dashed));
+    }
+
+    #[test]
+    fn test_parse_box_shadow() {
+        let shadows = BoxShadow::parse("0 4px 12px rgba(0, 0, 0, 0.25), inset 1 2 3 -4 #fff").unwrap().0;
+        assert_eq!(shadows.len(), 2);
+        assert_eq!((shadows[0].offset_x, shadows[0].offset_y, shadows[0].blur, shadows[0].spread), (0.0, 4.0, 12.0, 0.0));
+        assert_eq!(shadows[0].color.a, 63);
+        assert!(!shadows[0].inset);
+        assert_eq!((shadows[1].blur, shadows[1].spread), (3.0, -4.0));
+        assert!(shadows[1].inset);
+
+        // Color may come first; defaults to black
+        let shadow = Shadow::parse("red 2px 2px").unwrap();
+        assert_eq!((shadow.color.r, shadow.offset_x), (255, 2.0));
+        assert_eq!(Shadow::parse("2px 2px").unwrap().color.a, 255);
+
+        assert!(BoxShadow::parse("none").unwrap().0.is_empty());
+        assert!(Shadow::parse("2px").is_none());
+        assert!(Shadow::parse("1px 1px -2px red").is_none());
+        assert!(Shadow::parse("red 1px 1px blue").is_none());
+    }
+
+    #[test]
+    fn test_parse_gradient() {
+        let gradient = Gradient::parse("linear-gradient(135deg, #f00 0%, rgba(0, 0, 255, 0.5) 100%)").unwrap();
+        match gradient {
+            Gradient::Linear { direction, stops } => {
+                assert_eq!(direction, LinearDirection::Angle(135.0));
+                assert_eq!(stops.len(), 2);
+                assert_eq!(stops[1].color.a, 127);
+                assert_eq!(stops[1].position, Some(PositionOffset::Percent(100.0)));
+            }
+            _ => panic!("Expected linear gradient"),
+        }
+
+        assert!(matches!(
+            Gradient::parse("linear-gradient(to top right, red, blue)"),
+            Some(Gradient::Linear { direction: LinearDirection::Corner { x: 1.0, y: -1.0 }, .. })
+        ));
+
+        match Gradient::parse("radial-gradient(circle at top left, white, black 80%)").unwrap() {
+            Gradient::Radial { shape, size, center, stops } => {
+                assert_eq!(shape, RadialShape::Circle);
+                assert_eq!(size, RadialSize::FarthestCorner);
+                assert_eq!(center.x, PositionOffset::Percent(0.0));
+                assert_eq!(stops[0].position, None);
+            }
+            _ => panic!("Expected radial gradient"),
+        }
+
+        assert!(Gradient::parse("linear-gradient(red)").is_none());
+        assert!(Gradient::parse("conic-gradient(red, blue)").is_none());
+    }
+
     #[test]
     fn test_parse_color() {
         assert_eq!(Color::parse("#fff").unwrap().r, 255);
         assert_eq!(Color::parse("#000000").unwrap().r, 0);
         assert_eq!(Color::parse("rgb(255, 0, 0)").unwrap().r, 255);
         assert_eq!(Color::parse("rgba(0, 0, 255, 0.5)").unwrap().a, 127);
+        assert_eq!(Color::parse("hsl(120 100% 25%)").unwrap().to_css(), "#008000");
+        assert_eq!(Color::parse("CurrentColor"), Some(Color::CURRENT));
+        assert!(Color::parse("rgb(0 0 0 0)").is_none());
     }
 
     #[test]
     fn test_spacing_edges() {
-        assert_eq!(Spacing::Uniform(10.0).to_edges(), [10.0, 10.0, 10.0, 10.0]);
+        let px = Dimension::px;
+        assert_eq!(Spacing::parse("10").unwrap().0, [px(10.0); 4]);
+        assert_eq!(
+            Spacing::parse("10 20").unwrap().0,
+            [px(10.0), px(20.0), px(10.0), px(20.0)]
+        );
+        assert_eq!(
+            Spacing::parse("10 20 30 40").unwrap().0,
+            [px(10.0), px(20.0), px(30.0), px(40.0)]
+        );
+        assert_eq!(
+            Spacing::parse("1em calc(10% + 4px)").unwrap().0[1],
+            Dimension { px: 4.0, percent: 10.0, ..Default::default() }
+        );
+        assert!(Spacing::parse("10 20 30 40 50").is_none());
         assert_eq!(
-            Spacing::Multi("10 20".to_string()).to_edges(),
-            [10.0, 20.0, 10.0, 20.0]
+            Margin::parse("0 auto").unwrap().0[3],
+            DimensionOrAuto::Auto
         );
+        assert!(Spacing::parse("0 auto").is_none());
+        assert!(serde_json::from_str::<Spacing>(r#""10 abc""#).is_err());
+    }
+
+    #[test]
+    fn test_parse_dimension() {
+        let parse = |s: &str| Dimension::parse(s).unwrap();
+        assert_eq!(parse("12"), Dimension::px(12.0));
+        assert_eq!(parse("50%"), Dimension::percent(50.0));
+        assert_eq!(parse("1.5rem").rem, 1.5);
+        assert_eq!(parse("2em").em, 2.0);
+        assert_eq!(parse("10vw").vw, 10.0);
+        assert_eq!(parse("-5vh").vh, -5.0);
         assert_eq!(
-            Spacing::Multi("10 20 30 40".to_string()).to_edges(),
-            [10.0, 20.0, 30.0, 40.0]
+            parse("calc(100% - 2 * (1em + 4px))"),
+            Dimension { px: -8.0, percent: 100.0, em: -2.0, ..Default::default() }
         );
+        assert_eq!(parse("calc(100px / 4)"), Dimension::px(25.0));
+
+        for invalid in ["abc", "10pt", "calc(10px * 2px)", "calc(10 + 5px)", "calc(1px / 0)", "calc(1px", "calc(2)"] {
+            assert!(Dimension::parse(invalid).is_none(), "{}", invalid);
+        }
+        let error = serde_json::from_str::<BoxStyle>(r#"{ "width": "12qq" }"#).unwrap_err();
+        assert!(error.to_string().contains("invalid length: 12qq"));
+        assert!(serde_json::from_str::<BorderRadius>(r#""50% 1em""#).is_ok());
     }
 }
@@ -2,23 +2,30 @@
 //!
 //! Builds a Taffy layout tree from the Element tree and computes layout.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
 
 use taffy::prelude::*;
 use taffy::style::Style;
 
 use crate::element::{
-    self, AlignItems as ElemAlignItems, BoxStyle, Color, Dimension, Element,
-    FlexDirection as ElemFlexDirection, FlexStyle, FlexWrap as ElemFlexWrap, ImageStyle,
-    JustifyContent as ElemJustifyContent, Spacing, TextAlign, TextRendering, TextStyle,
+    self, AlignContent as ElemAlignContent, AlignItems as ElemAlignItems, Border, BorderRadius,
+    BorderStyle, BoxStyle, Color, Dimension, DimensionOrAuto, Direction, Element,
+    FlexDirection as ElemFlexDirection, FlexItemStyle, FlexStyle, FlexWrap as ElemFlexWrap,
+    FontSizeMode, FontStretch, FontStyle, Gradient, GridAutoFlow as ElemGridAutoFlow, GridLine,
+    GridPlacement as ElemGridPlacement, GridStyle, GridTemplate, GridTrack, ImageStyle,
+    InheritableTextStyle, JustifyContent as ElemJustifyContent, Margin, ObjectFit, ObjectPosition,
+    Overflow, Position as ElemPosition, RepeatCount, Shadow, Spacing, TextAlign, TextContent,
+    TextDecorationLine, TextDecorationStyle, TextOverflow, TextRendering, TextSpan, TextStyle,
+    TrackBreadth, TrackSize, Transform, WhiteSpace,
 };
-use crate::text::TextLayoutEngine;
+use crate::text::{font_stack_with_fallbacks, ShapedText, TextLayoutEngine};
 
 /// Context attached to Taffy leaf nodes that need measurement.
 #[derive(Debug, Clone)]
 pub enum NodeContext {
     /// Text node that needs Parley for measurement
-    Text(TextContext),
+    Text(Box<TextContext>),
     /// Image with intrinsic dimensions
     Image { width: f32, height: f32 },
 }
@@ -27,22 +34,75 @@ pub enum NodeContext {
 #[derive(Debug, Clone)]
 pub struct TextContext {
     pub content: String,
-    pub font_family: Option<String>,
-    pub font_size: f32,
-    pub font_weight: u16,
-    pub line_height: f32,
-    pub letter_spacing: f32,
+    /// Resolved style, the same one used for rendering
+    pub style: TextStyleResolved,
+    pub spans: Vec<TextSpanResolved>,
+    pub font_size_mode: FontSizeMode,
+    pub min_font_size: f32,
+    pub max_font_size: f32,
+    /// Text shaped by the last measurement, reused by rendering
+    pub shaped: Option<ShapedText>,
+}
+
+impl TextContext {
+    /// Copy of this context at another font size, scaling span sizes along.
+    /// Spans keep their sizes if the base size is zero.
+    pub fn with_font_size(&self, font_size: f32) -> TextContext {
+        let scale = if self.style.font_size > 0.0 {
+            font_size / self.style.font_size
+        } else {
+            1.0
+        };
+        TextContext {
+            style: TextStyleResolved {
+                font_size,
+                ..self.style.clone()
+            },
+            spans: self
+                .spans
+                .iter()
+                .map(|span| TextSpanResolved {
+                    font_size: span.font_size * scale,
+                    ..span.clone()
+                })
+                .collect(),
+            shaped: None,
+            ..self.clone()
+        }
+    }
+
+    /// Measure the text, keeping it shaped for rendering.
+    pub fn measure(
+        &mut self,
+        text_engine: &mut TextLayoutEngine,
+        max_width: Option<f32>,
+    ) -> Size<f32> {
+        let shaped = text_engine.shape_text(&self.content, &self.style, &self.spans, max_width);
+        let size = Size {
+            width: shaped.width,
+            height: shaped.height,
+        };
+        self.shaped = Some(shaped);
+        size
+    }
+
+    /// The text shaped by the last measurement, if it can be positioned at
+    /// `max_width` as is.
+    pub fn cached_shape(&mut self, max_width: f32) -> Option<&mut ShapedText> {
+        self.shaped.as_mut().filter(|shaped| shaped.fits(max_width))
+    }
 }
 
 impl Default for TextContext {
     fn default() -> Self {
         Self {
             content: String::new(),
-            font_family: None,
-            font_size: 16.0,
-            font_weight: 400,
-            line_height: 1.2,
-            letter_spacing: 0.0,
+            style: TextStyleResolved::default(),
+            spans: Vec::new(),
+            font_size_mode: FontSizeMode::Fixed,
+            min_font_size: 1.0,
+            max_font_size: 16.0,
+            shaped: None,
         }
     }
 }
@@ -70,8 +130,19 @@ pub struct NodeData {
 pub enum ElementType {
     Box,
     Flex,
-    Text { content: String, style: TextStyleResolved },
-    Image { src: String },
+    Grid,
+    Text {
+        content: String,
+        style: TextStyleResolved,
+        spans: Vec<TextSpanResolved>,
+    },
+    Image {
+        src: String,
+        width: f32,
+        height: f32,
+        object_fit: ObjectFit,
+        object_position: ObjectPosition,
+    },
 }
 
 /// Resolved text style with defaults applied.
@@ -80,11 +151,70 @@ pub struct TextStyleResolved {
     pub font_family: String,
     pub font_size: f32,
     pub font_weight: u16,
+    pub font_style: FontStyle,
+    pub font_stretch: FontStretch,
     pub line_height: f32,
     pub text_align: TextAlign,
+    pub direction: Direction,
     pub color: Color,
     pub letter_spacing: f32,
+    pub word_spacing: f32,
     pub text_rendering: TextRendering,
+    /// Lines beyond this are dropped
+    pub max_lines: Option<usize>,
+    /// How text cut by `max_lines` or `nowrap` ends; overflows visibly if unset
+    pub text_overflow: Option<TextOverflow>,
+    pub white_space: WhiteSpace,
+    pub text_decoration_line: TextDecorationLine,
+    pub text_decoration_color: Color,
+    /// Decoration thickness; the font's underline/strikeout size if unset
+    pub text_decoration_thickness: Option<f32>,
+    pub text_decoration_style: TextDecorationStyle,
+}
+
+/// Text properties inherited from ancestor containers, as CSS inheritance.
+#[derive(Debug, Clone)]
+struct InheritedStyle {
+    color: Color,
+    font_family: String,
+    font_size: f32,
+    font_weight: u16,
+    line_height: f32,
+    text_align: TextAlign,
+    letter_spacing: f32,
+    direction: Direction,
+}
+
+impl Default for InheritedStyle {
+    fn default() -> Self {
+        Self {
+            color: Color::BLACK,
+            font_family: "sans-serif".to_string(),
+            font_size: 16.0,
+            font_weight: 400,
+            line_height: 1.2,
+            text_align: TextAlign::Start,
+            letter_spacing: 0.0,
+            direction: Direction::Ltr,
+        }
+    }
+}
+
+impl InheritedStyle {
+    /// The style a container passes to its children: its own text
+    /// properties where set, the inherited ones otherwise.
+    fn cascade(&self, style: &InheritableTextStyle) -> Self {
+        Self {
+            color: style.color.as_ref().map_or_else(|| self.color.clone(), |c| c.resolve(&self.color)),
+            font_family: style.font_family.clone().unwrap_or_else(|| self.font_family.clone()),
+            font_size: style.font_size.unwrap_or(self.font_size),
+            font_weight: style.font_weight.map_or(self.font_weight, |w| w.0),
+            line_height: style.line_height.unwrap_or(self.line_height),
+            text_align: style.text_align.unwrap_or(self.text_align),
+            letter_spacing: style.letter_spacing.unwrap_or(self.letter_spacing),
+            direction: style.direction.unwrap_or(self.direction),
+        }
+    }
 }
 
 impl Default for TextStyleResolved {
@@ -93,34 +223,277 @@ impl Default for TextStyleResolved {
             font_family: "sans-serif".to_string(),
             font_size: 16.0,
             font_weight: 400,
+            font_style: FontStyle::Normal,
+            font_stretch: FontStretch::NORMAL,
             line_height: 1.2,
-            text_align: TextAlign::Left,
+            text_align: TextAlign::Start,
+            direction: Direction::Ltr,
             color: Color::BLACK,
             letter_spacing: 0.0,
+            word_spacing: 0.0,
             text_rendering: TextRendering::Text,
+            max_lines: None,
+            text_overflow: None,
+            white_space: WhiteSpace::Normal,
+            text_decoration_line: TextDecorationLine::default(),
+            text_decoration_color: Color::BLACK,
+            text_decoration_thickness: None,
+            text_decoration_style: TextDecorationStyle::Solid,
         }
     }
 }
 
+/// A styled byte range of a text element's content, with the span's style
+/// resolved against the element's style.
+#[derive(Debug, Clone)]
+pub struct TextSpanResolved {
+    pub range: Range<usize>,
+    pub font_family: String,
+    pub font_size: f32,
+    pub font_weight: u16,
+    pub font_style: FontStyle,
+    pub font_stretch: FontStretch,
+    pub color: Color,
+}
+
+/// A border side with `borderWidth`/`borderStyle`/`borderColor` fallbacks applied.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BorderSide {
+    pub width: f32,
+    pub style: BorderStyle,
+    /// Unpainted when unset
+    pub color: Option<Color>,
+}
+
 /// Visual properties for rendering.
 #[derive(Debug, Clone, Default)]
 pub struct VisualStyle {
     pub background_color: Option<Color>,
-    pub border_width: f32,
-    pub border_color: Option<Color>,
+    pub background_image: Option<Gradient>,
+    /// Box shadows in CSS order (first is on top)
+    pub box_shadow: Vec<Shadow>,
+    /// Border sides in top, right, bottom, left order
+    pub border: [BorderSide; 4],
     pub border_radius: [f32; 4],
     pub opacity: f32,
+    /// Whether children are clipped to the padding box
+    pub overflow: Overflow,
+    /// Paint order among siblings
+    pub z_index: i32,
+    /// Whether the element is relatively or absolutely positioned
+    pub positioned: bool,
+    /// Applied after layout around `transform_origin`
+    pub transform: Option<Transform>,
+    pub transform_origin: ObjectPosition,
+}
+
+impl VisualStyle {
+    /// Replace `currentColor` in the background, shadows and borders.
+    fn resolve_current_color(mut self, current: &Color) -> Self {
+        if let Some(color) = &mut self.background_color {
+            *color = color.resolve(current);
+        }
+        if let Some(Gradient::Linear { stops, .. } | Gradient::Radial { stops, .. }) =
+            &mut self.background_image
+        {
+            for stop in stops {
+                stop.color = stop.color.resolve(current);
+            }
+        }
+        for shadow in &mut self.box_shadow {
+            shadow.color = shadow.color.resolve(current);
+        }
+        for side in &mut self.border {
+            if let Some(color) = &mut side.color {
+                *color = color.resolve(current);
+            }
+        }
+        self
+    }
+}
+
+/// A style property holding a length.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LengthField {
+    Width,
+    Height,
+    MinWidth,
+    MinHeight,
+    MaxWidth,
+    MaxHeight,
+    /// Top, right, bottom or left
+    Inset(usize),
+    Margin(usize),
+    Padding(usize),
+    ColumnGap,
+    RowGap,
+    FlexBasis,
+    /// Top-left, top-right, bottom-right or bottom-left
+    Radius(usize),
+}
+
+impl LengthField {
+    /// Whether percentages are of a width rather than a height. Margins and
+    /// padding use the containing block's width on every side, as in CSS.
+    fn is_horizontal(self) -> bool {
+        match self {
+            LengthField::Width | LengthField::MinWidth | LengthField::MaxWidth => true,
+            LengthField::Inset(side) => side % 2 == 1,
+            LengthField::Margin(_) | LengthField::Padding(_) | LengthField::ColumnGap => true,
+            _ => false,
+        }
+    }
+
+    /// Set the property to a length in pixels.
+    fn apply(self, style: &mut Style, px: f32) {
+        let side = |rect: &mut Rect<LengthPercentageAuto>, side: usize| match side {
+            0 => rect.top = length(px),
+            1 => rect.right = length(px),
+            2 => rect.bottom = length(px),
+            _ => rect.left = length(px),
+        };
+        match self {
+            LengthField::Width => style.size.width = length(px),
+            LengthField::Height => style.size.height = length(px),
+            LengthField::MinWidth => style.min_size.width = length(px),
+            LengthField::MinHeight => style.min_size.height = length(px),
+            LengthField::MaxWidth => style.max_size.width = length(px),
+            LengthField::MaxHeight => style.max_size.height = length(px),
+            LengthField::Inset(i) => side(&mut style.inset, i),
+            LengthField::Margin(i) => side(&mut style.margin, i),
+            LengthField::Padding(0) => style.padding.top = length(px),
+            LengthField::Padding(1) => style.padding.right = length(px),
+            LengthField::Padding(2) => style.padding.bottom = length(px),
+            LengthField::Padding(_) => style.padding.left = length(px),
+            LengthField::ColumnGap => style.gap.width = length(px),
+            LengthField::RowGap => style.gap.height = length(px),
+            LengthField::FlexBasis => style.flex_basis = length(px),
+            LengthField::Radius(_) => {}
+        }
+    }
+}
+
+/// A `calc()` sum of pixels and a percentage, resolved once a first layout
+/// pass has sized the box its percentage refers to.
+#[derive(Debug, Clone, Copy)]
+struct CalcLength {
+    field: LengthField,
+    px: f32,
+    percent: f32,
+}
+
+/// Converts an element's lengths for Taffy: `em`, `rem`, `vw` and `vh`
+/// become pixels, and sums of pixels and percentages, which Taffy can't
+/// express, are laid out as their percentage and collected in `calc`.
+struct Lengths {
+    /// Size of `em`
+    font_size: f32,
+    /// Size of `rem`
+    root_font_size: f32,
+    viewport: Size<f32>,
+    calc: Vec<CalcLength>,
+}
+
+impl Lengths {
+    /// Resolve everything but the percentage to pixels.
+    fn resolve(&self, dim: &Dimension) -> (f32, f32) {
+        let px = dim.px
+            + dim.em * self.font_size
+            + dim.rem * self.root_font_size
+            + dim.vw * self.viewport.width / 100.0
+            + dim.vh * self.viewport.height / 100.0;
+        (px, dim.percent)
+    }
+
+    fn length(&mut self, dim: &Dimension, field: LengthField) -> LengthPercentage {
+        match self.resolve(dim) {
+            (px, 0.0) => LengthPercentage::length(px),
+            (0.0, percent) => LengthPercentage::percent(percent / 100.0),
+            (px, percent) => {
+                self.calc.push(CalcLength { field, px, percent });
+                LengthPercentage::percent(percent / 100.0)
+            }
+        }
+    }
+
+    fn dimension(&mut self, dim: &Option<Dimension>, field: LengthField) -> taffy::Dimension {
+        match dim {
+            None => taffy::Dimension::auto(),
+            Some(dim) => self.length(dim, field).into(),
+        }
+    }
+
+    fn gap(&mut self, dim: &Option<Dimension>, field: LengthField) -> LengthPercentage {
+        self.length(&dim.unwrap_or_default(), field)
+    }
+
+    fn margin(&mut self, margin: &Option<Margin>) -> Rect<LengthPercentageAuto> {
+        let [top, right, bottom, left] = margin.unwrap_or_default().0;
+        let mut side = |side: DimensionOrAuto, field| match side {
+            DimensionOrAuto::Auto => LengthPercentageAuto::auto(),
+            DimensionOrAuto::Length(dim) => self.length(&dim, field).into(),
+        };
+        Rect {
+            top: side(top, LengthField::Margin(0)),
+            right: side(right, LengthField::Margin(1)),
+            bottom: side(bottom, LengthField::Margin(2)),
+            left: side(left, LengthField::Margin(3)),
+        }
+    }
+
+    fn flex_basis(&mut self, basis: &Option<DimensionOrAuto>) -> taffy::Dimension {
+        match basis {
+            None | Some(DimensionOrAuto::Auto) => taffy::Dimension::auto(),
+            Some(DimensionOrAuto::Length(dim)) => self.length(dim, LengthField::FlexBasis).into(),
+        }
+    }
+
+    fn padding(&mut self, spacing: &Option<Spacing>) -> Rect<LengthPercentage> {
+        let [top, right, bottom, left] = spacing.unwrap_or_default().0;
+        Rect {
+            top: self.length(&top, LengthField::Padding(0)),
+            right: self.length(&right, LengthField::Padding(1)),
+            bottom: self.length(&bottom, LengthField::Padding(2)),
+            left: self.length(&left, LengthField::Padding(3)),
+        }
+    }
+
+    /// Corner radii in pixels; percentages of the box are filled in after
+    /// layout.
+    fn border_radius(&mut self, radius: &Option<BorderRadius>) -> [f32; 4] {
+        let corners = radius.unwrap_or_default().0;
+        std::array::from_fn(|i| {
+            let (px, percent) = self.resolve(&corners[i]);
+            if percent != 0.0 {
+                self.calc.push(CalcLength { field: LengthField::Radius(i), px, percent });
+            }
+            px
+        })
+    }
 }
 
 /// Layout engine that builds and computes layout.
 pub struct LayoutEngine {
     pub text_engine: TextLayoutEngine,
+    /// Font families tried after each text's own family stack, for
+    /// characters its fonts have no glyphs for
+    pub font_fallbacks: Vec<String>,
+    /// Root font size: the size of `rem` and of text without a `fontSize`
+    pub font_size: f32,
+    /// Size `vw` and `vh` are relative to during `compute_layout`
+    viewport: Size<f32>,
+    /// `calc()` lengths collected while building the tree
+    calc_lengths: Vec<(NodeId, CalcLength)>,
 }
 
 impl LayoutEngine {
     pub fn new() -> Self {
         Self {
             text_engine: TextLayoutEngine::new(),
+            font_fallbacks: Vec::new(),
+            font_size: 16.0,
+            viewport: Size::ZERO,
+            calc_lengths: Vec::new(),
         }
     }
 
@@ -134,9 +507,20 @@ impl LayoutEngine {
     ) -> Result<LayoutResult, LayoutError> {
         let mut taffy: TaffyTree<NodeContext> = TaffyTree::new();
         let mut node_data = HashMap::new();
+        // Without a fixed height, `vh` falls back to the width
+        self.viewport = Size {
+            width: viewport_width,
+            height: viewport_height.unwrap_or(viewport_width),
+        };
+        self.calc_lengths.clear();
 
-        // Build tree recursively
-        let root = self.build_node(&mut taffy, &mut node_data, element, default_font_family)?;
+        // Build tree recursively, cascading text properties from the root values
+        let root_style = InheritedStyle {
+            font_family: default_font_family.unwrap_or("sans-serif").to_string(),
+            font_size: self.font_size,
+            ..Default::default()
+        };
+        let root = self.build_node(&mut taffy, &mut node_data, element, &root_style)?;
 
         // Compute layout
         let available_space = Size {
@@ -148,14 +532,80 @@ impl LayoutEngine {
 
         // Create a reference to text_engine for the closure
         let text_engine = &mut self.text_engine;
+        let mut compute = |taffy: &mut TaffyTree<NodeContext>| {
+            taffy.compute_layout_with_measure(
+                root,
+                available_space,
+                |known_dimensions, available_space, _node_id, node_context, _style| {
+                    measure_function(known_dimensions, available_space, node_context, text_engine)
+                },
+            )
+        };
+        compute(&mut taffy)?;
 
-        taffy.compute_layout_with_measure(
-            root,
-            available_space,
-            |known_dimensions, available_space, _node_id, node_context, _style| {
-                measure_function(known_dimensions, available_space, node_context, text_engine)
-            },
-        )?;
+        // calc() sums of pixels and percentages were laid out as their
+        // percentage. Resolve them a tree level at a time, laying out again
+        // after each, so a nested calc() sees the size its parent's calc()
+        // resolved to
+        let containing_block = Size {
+            width: viewport_width,
+            height: viewport_height.unwrap_or(0.0),
+        };
+        let mut levels: BTreeMap<usize, Vec<(NodeId, CalcLength)>> = BTreeMap::new();
+        for &(node_id, calc) in &self.calc_lengths {
+            if !matches!(calc.field, LengthField::Radius(_)) {
+                let level = calc_level(&taffy, node_id, calc.field);
+                levels.entry(level).or_default().push((node_id, calc));
+            }
+        }
+        for calcs in levels.values() {
+            let restyles = calcs
+                .iter()
+                .map(|&(node_id, calc)| {
+                    let basis = percent_basis(&taffy, node_id, calc.field, containing_block)?;
+                    Ok((node_id, calc.field, calc.px + calc.percent / 100.0 * basis))
+                })
+                .collect::<Result<Vec<_>, LayoutError>>()?;
+            for (node_id, field, px) in restyles {
+                let mut style = taffy.style(node_id)?.clone();
+                field.apply(&mut style, px);
+                taffy.set_style(node_id, style)?;
+            }
+            compute(&mut taffy)?;
+        }
+
+        // Percentage radii are of the box's shorter side
+        for &(node_id, calc) in &self.calc_lengths {
+            if let LengthField::Radius(corner) = calc.field
+                && let Some(data) = node_data.get_mut(&node_id)
+            {
+                let size = taffy.layout(node_id)?.size;
+                data.visual.border_radius[corner] =
+                    calc.px + calc.percent / 100.0 * size.width.min(size.height);
+            }
+        }
+
+        // Fitted text picks its final size against the box it was given
+        for (&node_id, data) in node_data.iter_mut() {
+            let ElementType::Text { style, spans, .. } = &mut data.element_type else {
+                continue;
+            };
+            let size = taffy.layout(node_id)?.size;
+            let unrounded_width = taffy.unrounded_layout(node_id).size.width;
+            let Some(NodeContext::Text(ctx)) = taffy.get_node_context_mut(node_id) else {
+                continue;
+            };
+            if ctx.font_size_mode != FontSizeMode::Fit {
+                continue;
+            }
+
+            // Shape at the final size once more, for rendering to reuse
+            let font_size = fit_font_size(text_engine, ctx, Some(size.width), Some(size.height));
+            **ctx = ctx.with_font_size(font_size);
+            ctx.measure(text_engine, Some(unrounded_width));
+            style.clone_from(&ctx.style);
+            spans.clone_from(&ctx.spans);
+        }
 
         Ok(LayoutResult {
             taffy,
@@ -164,19 +614,38 @@ impl LayoutEngine {
         })
     }
 
+    /// Length resolution for an element whose `em` is `font_size`.
+    fn lengths(&self, font_size: f32) -> Lengths {
+        Lengths {
+            font_size,
+            root_font_size: self.font_size,
+            viewport: self.viewport,
+            calc: Vec::new(),
+        }
+    }
+
+    fn add_calc_lengths(&mut self, node_id: NodeId, lengths: Lengths) {
+        self.calc_lengths
+            .extend(lengths.calc.into_iter().map(|calc| (node_id, calc)));
+    }
+
     fn build_node(
-        &self,
+        &mut self,
         taffy: &mut TaffyTree<NodeContext>,
         node_data: &mut HashMap<NodeId, NodeData>,
         element: &Element,
-        default_font_family: Option<&str>,
+        inherited: &InheritedStyle,
     ) -> Result<NodeId, LayoutError> {
         match element {
             Element::Box { style, children } => {
-                let taffy_style = box_style_to_taffy(style);
+                let inherited = &inherited.cascade(&style.text);
+                let mut lengths = self.lengths(inherited.font_size);
+                let taffy_style = box_style_to_taffy(style, &mut lengths);
                 let child_ids = children
                     .iter()
-                    .map(|child| self.build_node(taffy, node_data, child, default_font_family))
+                    .map(|child| {
+                        self.build_node(taffy, node_data, child, inherited)
+                    })
                     .collect::<Result<Vec<_>, _>>()?;
 
                 let node_id = taffy.new_with_children(taffy_style, &child_ids)?;
@@ -187,26 +656,39 @@ impl LayoutEngine {
                         element_type: ElementType::Box,
                         visual: VisualStyle {
                             background_color: style.background_color.clone(),
-                            border_width: style.border_width.unwrap_or(0.0),
-                            border_color: style.border_color.clone(),
-                            border_radius: style
-                                .border_radius
-                                .as_ref()
-                                .map(|r| r.to_corners())
-                                .unwrap_or([0.0; 4]),
+                            background_image: style.background_image.clone(),
+                            box_shadow: style.box_shadow.clone().map(|s| s.0).unwrap_or_default(),
+                            border: resolve_borders(
+                                style.border_width,
+                                &style.border_color,
+                                style.border_style,
+                                [&style.border_top, &style.border_right, &style.border_bottom, &style.border_left],
+                            ),
+                            border_radius: lengths.border_radius(&style.border_radius),
                             opacity: style.opacity.unwrap_or(1.0),
-                        },
+                            overflow: style.overflow.unwrap_or_default(),
+                            z_index: style.z_index.unwrap_or(0),
+                            positioned: is_positioned(style.position),
+                            transform: style.transform.clone(),
+                            transform_origin: style.transform_origin.unwrap_or_default(),
+                        }
+                        .resolve_current_color(&inherited.color),
                     },
                 );
+                self.add_calc_lengths(node_id, lengths);
 
                 Ok(node_id)
             }
 
             Element::Flex { style, children } => {
-                let taffy_style = flex_style_to_taffy(style);
-                let child_ids = children
-                    .iter()
-                    .map(|child| self.build_node(taffy, node_data, child, default_font_family))
+                let inherited = &inherited.cascade(&style.text);
+                let mut lengths = self.lengths(inherited.font_size);
+                let taffy_style = flex_style_to_taffy(style, inherited.direction, &mut lengths);
+                let child_ids = ordered_items(children)
+                    .into_iter()
+                    .map(|child| {
+                        self.build_node(taffy, node_data, child, inherited)
+                    })
                     .collect::<Result<Vec<_>, _>>()?;
 
                 let node_id = taffy.new_with_children(taffy_style, &child_ids)?;
@@ -217,56 +699,134 @@ impl LayoutEngine {
                         element_type: ElementType::Flex,
                         visual: VisualStyle {
                             background_color: style.background_color.clone(),
-                            border_width: style.border_width.unwrap_or(0.0),
-                            border_color: style.border_color.clone(),
-                            border_radius: style
-                                .border_radius
-                                .as_ref()
-                                .map(|r| r.to_corners())
-                                .unwrap_or([0.0; 4]),
+                            background_image: style.background_image.clone(),
+                            box_shadow: style.box_shadow.clone().map(|s| s.0).unwrap_or_default(),
+                            border: resolve_borders(
+                                style.border_width,
+                                &style.border_color,
+                                style.border_style,
+                                [&style.border_top, &style.border_right, &style.border_bottom, &style.border_left],
+                            ),
+                            border_radius: lengths.border_radius(&style.border_radius),
                             opacity: style.opacity.unwrap_or(1.0),
-                        },
+                            overflow: style.overflow.unwrap_or_default(),
+                            z_index: style.z_index.unwrap_or(0),
+                            positioned: is_positioned(style.position),
+                            transform: style.transform.clone(),
+                            transform_origin: style.transform_origin.unwrap_or_default(),
+                        }
+                        .resolve_current_color(&inherited.color),
+                    },
+                );
+                self.add_calc_lengths(node_id, lengths);
+
+                Ok(node_id)
+            }
+
+            Element::Grid { style, children } => {
+                let inherited = &inherited.cascade(&style.text);
+                let mut lengths = self.lengths(inherited.font_size);
+                let taffy_style = grid_style_to_taffy(style, &mut lengths);
+                let child_ids = ordered_items(children)
+                    .into_iter()
+                    .map(|child| {
+                        self.build_node(taffy, node_data, child, inherited)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let node_id = taffy.new_with_children(taffy_style, &child_ids)?;
+
+                node_data.insert(
+                    node_id,
+                    NodeData {
+                        element_type: ElementType::Grid,
+                        visual: VisualStyle {
+                            background_color: style.background_color.clone(),
+                            background_image: style.background_image.clone(),
+                            box_shadow: style.box_shadow.clone().map(|s| s.0).unwrap_or_default(),
+                            border: resolve_borders(
+                                style.border_width,
+                                &style.border_color,
+                                style.border_style,
+                                [&style.border_top, &style.border_right, &style.border_bottom, &style.border_left],
+                            ),
+                            border_radius: lengths.border_radius(&style.border_radius),
+                            opacity: style.opacity.unwrap_or(1.0),
+                            overflow: style.overflow.unwrap_or_default(),
+                            z_index: style.z_index.unwrap_or(0),
+                            positioned: is_positioned(style.position),
+                            transform: style.transform.clone(),
+                            transform_origin: style.transform_origin.unwrap_or_default(),
+                        }
+                        .resolve_current_color(&inherited.color),
                     },
                 );
+                self.add_calc_lengths(node_id, lengths);
 
                 Ok(node_id)
             }
 
             Element::Text { content, style } => {
-                let text_context = NodeContext::Text(TextContext {
+                let font_family = style.font_family.clone().unwrap_or_else(|| inherited.font_family.clone());
+                let color = style.color.as_ref().map_or_else(|| inherited.color.clone(), |c| c.resolve(&inherited.color));
+                let resolved = TextStyleResolved {
+                    font_family: font_stack_with_fallbacks(font_family, &self.font_fallbacks),
+                    font_size: style.font_size.unwrap_or(inherited.font_size),
+                    font_weight: style.font_weight.map_or(inherited.font_weight, |w| w.0),
+                    font_style: style.font_style.unwrap_or_default(),
+                    font_stretch: style.font_stretch.unwrap_or_default(),
+                    line_height: style.line_height.unwrap_or(inherited.line_height),
+                    text_align: style.text_align.unwrap_or(inherited.text_align),
+                    direction: style.direction.unwrap_or(inherited.direction),
+                    color: color.clone(),
+                    letter_spacing: style.letter_spacing.unwrap_or(inherited.letter_spacing),
+                    word_spacing: style.word_spacing.unwrap_or(0.0),
+                    text_rendering: style.text_rendering.unwrap_or_default(),
+                    max_lines: style.max_lines,
+                    text_overflow: style.text_overflow,
+                    white_space: style.white_space.unwrap_or_default(),
+                    text_decoration_line: style.text_decoration_line.unwrap_or_default(),
+                    text_decoration_color: style
+                        .text_decoration_color
+                        .as_ref()
+                        .map_or_else(|| color.clone(), |c| c.resolve(&color)),
+                    text_decoration_thickness: style.text_decoration_thickness,
+                    text_decoration_style: style.text_decoration_style.unwrap_or_default(),
+                };
+                let (content, spans) = match content {
+                    TextContent::Plain(content) => (content.clone(), Vec::new()),
+                    TextContent::Spans(spans) => resolve_spans(spans, &resolved, &self.font_fallbacks),
+                };
+
+                let text_context = NodeContext::Text(Box::new(TextContext {
                     content: content.clone(),
-                    font_family: style.font_family.clone(),
-                    font_size: style.font_size.unwrap_or(16.0),
-                    font_weight: style.font_weight.map(|w| w.0).unwrap_or(400),
-                    line_height: style.line_height.unwrap_or(1.2),
-                    letter_spacing: style.letter_spacing.unwrap_or(0.0),
-                });
-
-                let taffy_style = text_style_to_taffy(style);
+                    style: resolved.clone(),
+                    spans: spans.clone(),
+                    font_size_mode: style.font_size_mode.unwrap_or_default(),
+                    min_font_size: style.min_font_size.unwrap_or(1.0),
+                    max_font_size: style.max_font_size.unwrap_or(resolved.font_size),
+                    shaped: None,
+                }));
+
+                let mut lengths = self.lengths(resolved.font_size);
+                let taffy_style = text_style_to_taffy(style, &mut lengths);
                 let node_id = taffy.new_leaf_with_context(taffy_style, text_context)?;
+                self.add_calc_lengths(node_id, lengths);
 
                 node_data.insert(
                     node_id,
                     NodeData {
                         element_type: ElementType::Text {
-                            content: content.clone(),
-                            style: TextStyleResolved {
-                                font_family: style
-                                    .font_family
-                                    .clone()
-                                    .or_else(|| default_font_family.map(String::from))
-                                    .unwrap_or_else(|| "sans-serif".to_string()),
-                                font_size: style.font_size.unwrap_or(16.0),
-                                font_weight: style.font_weight.map(|w| w.0).unwrap_or(400),
-                                line_height: style.line_height.unwrap_or(1.2),
-                                text_align: style.text_align.unwrap_or_default(),
-                                color: style.color.clone().unwrap_or(Color::BLACK),
-                                letter_spacing: style.letter_spacing.unwrap_or(0.0),
-                                text_rendering: style.text_rendering.unwrap_or_default(),
-                            },
+                            content,
+                            style: resolved,
+                            spans,
                         },
                         visual: VisualStyle {
                             opacity: 1.0,
+                            z_index: style.z_index.unwrap_or(0),
+                            positioned: is_positioned(style.position),
+                            transform: style.transform.clone(),
+                            transform_origin: style.transform_origin.unwrap_or_default(),
                             ..Default::default()
                         },
                     },
@@ -286,24 +846,34 @@ impl LayoutEngine {
                     height: *height,
                 };
 
-                let taffy_style = image_style_to_taffy(style, *width, *height);
+                let mut lengths = self.lengths(inherited.font_size);
+                let taffy_style = image_style_to_taffy(style, *width, *height, &mut lengths);
                 let node_id = taffy.new_leaf_with_context(taffy_style, image_context)?;
 
                 node_data.insert(
                     node_id,
                     NodeData {
-                        element_type: ElementType::Image { src: src.clone() },
+                        element_type: ElementType::Image {
+                            src: src.clone(),
+                            width: *width,
+                            height: *height,
+                            object_fit: style.object_fit.unwrap_or_default(),
+                            object_position: style.object_position.unwrap_or_default(),
+                        },
                         visual: VisualStyle {
-                            border_radius: style
-                                .border_radius
-                                .as_ref()
-                                .map(|r| r.to_corners())
-                                .unwrap_or([0.0; 4]),
+                            border_radius: lengths.border_radius(&style.border_radius),
                             opacity: style.opacity.unwrap_or(1.0),
+                            box_shadow: style.box_shadow.clone().map(|s| s.0).unwrap_or_default(),
+                            z_index: style.z_index.unwrap_or(0),
+                            positioned: is_positioned(style.position),
+                            transform: style.transform.clone(),
+                            transform_origin: style.transform_origin.unwrap_or_default(),
                             ..Default::default()
-                        },
+                        }
+                        .resolve_current_color(&inherited.color),
                     },
                 );
+                self.add_calc_lengths(node_id, lengths);
 
                 Ok(node_id)
             }
@@ -331,15 +901,22 @@ fn measure_function(
                 return Size { width, height };
             }
 
-            // Determine available width for text wrapping
+            // Determine the width to wrap at: the known width, else the
+            // available space
             let available_width = match available_space.width {
                 AvailableSpace::Definite(w) => Some(w),
                 AvailableSpace::MinContent => Some(0.0),
                 AvailableSpace::MaxContent => None,
             };
+            let width = known_dimensions.width.or(available_width);
 
-            // Measure text using the text engine
-            text_engine.measure(&ctx.content, ctx.font_size, available_width)
+            if ctx.font_size_mode == FontSizeMode::Fit {
+                let font_size = fit_font_size(text_engine, ctx, width, known_dimensions.height);
+                return text_engine.measure(&ctx.with_font_size(font_size), width);
+            }
+
+            // Measure text using the text engine, caching the layout
+            ctx.measure(text_engine, width)
         }
         Some(NodeContext::Image { width, height }) => {
             // Use intrinsic dimensions, respecting any known constraints
@@ -352,61 +929,189 @@ fn measure_function(
     }
 }
 
+/// Slack for layout rounding when checking whether fitted text fits.
+const FIT_TOLERANCE: f32 = 0.5;
+
+/// Search for the largest font size in `min_font_size..=max_font_size` at which
+/// the text fits `width`, `height` and `max_lines`, remeasuring at each step.
+/// Falls back to `min_font_size` if nothing fits.
+pub fn fit_font_size(
+    text_engine: &mut TextLayoutEngine,
+    ctx: &TextContext,
+    width: Option<f32>,
+    height: Option<f32>,
+) -> f32 {
+    let min = ctx.min_font_size.max(0.1);
+    let max = ctx.max_font_size.max(min);
+
+    let mut fits = |font_size: f32| {
+        // Measure the untruncated text so overflow is visible
+        let mut probe = ctx.with_font_size(font_size);
+        probe.style.max_lines = None;
+        probe.style.text_overflow = None;
+        let (size, lines) = text_engine.measure_lines(&probe, width);
+        width.is_none_or(|w| size.width <= w + FIT_TOLERANCE)
+            && height.is_none_or(|h| size.height <= h + FIT_TOLERANCE)
+            && ctx.style.max_lines.is_none_or(|max_lines| lines <= max_lines.max(1))
+    };
+
+    if fits(max) {
+        return max;
+    }
+    if !fits(min) {
+        return min;
+    }
+
+    // Binary search to a quarter pixel
+    let (mut lo, mut hi) = (min, max);
+    while hi - lo > 0.25 {
+        let mid = (lo + hi) / 2.0;
+        if fits(mid) {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+/// Flex or grid items sorted by `order`, keeping document order for equal
+/// values. Both layout and painting follow this order.
+fn ordered_items(children: &[Element]) -> Vec<&Element> {
+    let mut items: Vec<&Element> = children.iter().collect();
+    items.sort_by_key(|child| {
+        let item = match child {
+            Element::Box { style, .. } => &style.flex_item,
+            Element::Flex { style, .. } => &style.flex_item,
+            Element::Grid { style, .. } => &style.flex_item,
+            Element::Text { style, .. } => &style.flex_item,
+            Element::Image { style, .. } => &style.flex_item,
+        };
+        item.order.unwrap_or(0)
+    });
+    items
+}
+
+/// Concatenate span contents and resolve each span's style against the text
+/// element's style.
+fn resolve_spans(
+    spans: &[TextSpan],
+    base: &TextStyleResolved,
+    font_fallbacks: &[String],
+) -> (String, Vec<TextSpanResolved>) {
+    let mut content = String::new();
+    let mut resolved = Vec::with_capacity(spans.len());
+
+    for span in spans {
+        let start = content.len();
+        content.push_str(&span.content);
+        resolved.push(TextSpanResolved {
+            range: start..content.len(),
+            font_family: match &span.style.font_family {
+                Some(family) => font_stack_with_fallbacks(family.clone(), font_fallbacks),
+                None => base.font_family.clone(),
+            },
+            font_size: span.style.font_size.unwrap_or(base.font_size),
+            font_weight: span.style.font_weight.map(|w| w.0).unwrap_or(base.font_weight),
+            font_style: span.style.font_style.unwrap_or(base.font_style),
+            font_stretch: span.style.font_stretch.unwrap_or(base.font_stretch),
+            color: span
+                .style
+                .color
+                .as_ref()
+                .map_or_else(|| base.color.clone(), |c| c.resolve(&base.color)),
+        });
+    }
+
+    (content, resolved)
+}
+
 // ============================================================================
 // Style conversion functions
 // ============================================================================
 
-fn box_style_to_taffy(style: &BoxStyle) -> Style {
+fn box_style_to_taffy(style: &BoxStyle, lengths: &mut Lengths) -> Style {
+    let (position, inset) = position_to_taffy(
+        style.position,
+        [&style.top, &style.right, &style.bottom, &style.left],
+        lengths,
+    );
     Style {
+        position,
+        inset,
+        overflow: overflow_to_taffy(style.overflow),
         display: match style.display {
             Some(element::Display::None) => taffy::Display::None,
             _ => taffy::Display::Block,
         },
         size: Size {
-            width: dimension_to_taffy(&style.width),
-            height: dimension_to_taffy(&style.height),
+            width: lengths.dimension(&style.width, LengthField::Width),
+            height: lengths.dimension(&style.height, LengthField::Height),
         },
         min_size: Size {
-            width: dimension_to_taffy(&style.min_width),
-            height: dimension_to_taffy(&style.min_height),
+            width: lengths.dimension(&style.min_width, LengthField::MinWidth),
+            height: lengths.dimension(&style.min_height, LengthField::MinHeight),
         },
         max_size: Size {
-            width: dimension_to_taffy(&style.max_width),
-            height: dimension_to_taffy(&style.max_height),
+            width: lengths.dimension(&style.max_width, LengthField::MaxWidth),
+            height: lengths.dimension(&style.max_height, LengthField::MaxHeight),
         },
-        margin: spacing_to_taffy_rect(&style.margin),
-        padding: spacing_to_taffy_rect_lp(&style.padding),
-        border: spacing_to_taffy_rect_lp(&style.border_width.map(Spacing::Uniform)),
-        ..Default::default()
+        margin: lengths.margin(&style.margin),
+        padding: lengths.padding(&style.padding),
+        border: border_to_taffy(&resolve_borders(
+            style.border_width,
+            &style.border_color,
+            style.border_style,
+            [&style.border_top, &style.border_right, &style.border_bottom, &style.border_left],
+        )),
+        grid_row: grid_line_to_taffy(&style.grid_row),
+        grid_column: grid_line_to_taffy(&style.grid_column),
+        ..flex_item_to_taffy(&style.flex_item, lengths)
     }
 }
 
-fn flex_style_to_taffy(style: &FlexStyle) -> Style {
+fn flex_style_to_taffy(style: &FlexStyle, direction: Direction, lengths: &mut Lengths) -> Style {
+    let (position, inset) = position_to_taffy(
+        style.position,
+        [&style.top, &style.right, &style.bottom, &style.left],
+        lengths,
+    );
     Style {
+        position,
+        inset,
+        overflow: overflow_to_taffy(style.overflow),
         display: match style.display {
             Some(element::Display::None) => taffy::Display::None,
             _ => taffy::Display::Flex,
         },
         size: Size {
-            width: dimension_to_taffy(&style.width),
-            height: dimension_to_taffy(&style.height),
+            width: lengths.dimension(&style.width, LengthField::Width),
+            height: lengths.dimension(&style.height, LengthField::Height),
         },
         min_size: Size {
-            width: dimension_to_taffy(&style.min_width),
-            height: dimension_to_taffy(&style.min_height),
+            width: lengths.dimension(&style.min_width, LengthField::MinWidth),
+            height: lengths.dimension(&style.min_height, LengthField::MinHeight),
         },
         max_size: Size {
-            width: dimension_to_taffy(&style.max_width),
-            height: dimension_to_taffy(&style.max_height),
+            width: lengths.dimension(&style.max_width, LengthField::MaxWidth),
+            height: lengths.dimension(&style.max_height, LengthField::MaxHeight),
         },
-        margin: spacing_to_taffy_rect(&style.margin),
-        padding: spacing_to_taffy_rect_lp(&style.padding),
-        border: spacing_to_taffy_rect_lp(&style.border_width.map(Spacing::Uniform)),
-        flex_direction: match style.flex_direction {
-            Some(ElemFlexDirection::Row) | None => taffy::FlexDirection::Row,
-            Some(ElemFlexDirection::Column) => taffy::FlexDirection::Column,
-            Some(ElemFlexDirection::RowReverse) => taffy::FlexDirection::RowReverse,
-            Some(ElemFlexDirection::ColumnReverse) => taffy::FlexDirection::ColumnReverse,
+        margin: lengths.margin(&style.margin),
+        padding: lengths.padding(&style.padding),
+        border: border_to_taffy(&resolve_borders(
+            style.border_width,
+            &style.border_color,
+            style.border_style,
+            [&style.border_top, &style.border_right, &style.border_bottom, &style.border_left],
+        )),
+        flex_direction: match (style.flex_direction.unwrap_or_default(), direction) {
+            // Right-to-left rows start at the right edge
+            (ElemFlexDirection::Row, Direction::Ltr)
+            | (ElemFlexDirection::RowReverse, Direction::Rtl) => taffy::FlexDirection::Row,
+            (ElemFlexDirection::Row, Direction::Rtl)
+            | (ElemFlexDirection::RowReverse, Direction::Ltr) => taffy::FlexDirection::RowReverse,
+            (ElemFlexDirection::Column, _) => taffy::FlexDirection::Column,
+            (ElemFlexDirection::ColumnReverse, _) => taffy::FlexDirection::ColumnReverse,
         },
         justify_content: Some(match style.justify_content {
             Some(ElemJustifyContent::FlexStart) | None => taffy::JustifyContent::FlexStart,
@@ -415,111 +1120,354 @@ fn flex_style_to_taffy(style: &FlexStyle) -> Style {
             Some(ElemJustifyContent::SpaceBetween) => taffy::JustifyContent::SpaceBetween,
             Some(ElemJustifyContent::SpaceAround) => taffy::JustifyContent::SpaceAround,
             Some(ElemJustifyContent::SpaceEvenly) => taffy::JustifyContent::SpaceEvenly,
+            Some(ElemJustifyContent::Stretch) => taffy::JustifyContent::Stretch,
         }),
-        align_items: Some(match style.align_items {
-            Some(ElemAlignItems::FlexStart) => taffy::AlignItems::FlexStart,
-            Some(ElemAlignItems::FlexEnd) => taffy::AlignItems::FlexEnd,
-            Some(ElemAlignItems::Center) => taffy::AlignItems::Center,
-            Some(ElemAlignItems::Stretch) | None => taffy::AlignItems::Stretch,
-            Some(ElemAlignItems::Baseline) => taffy::AlignItems::Baseline,
+        align_items: Some(align_items_to_taffy(style.align_items.unwrap_or_default())),
+        align_content: Some(match style.align_content.unwrap_or_default() {
+            ElemAlignContent::Normal | ElemAlignContent::Stretch => taffy::AlignContent::Stretch,
+            ElemAlignContent::Start => taffy::AlignContent::Start,
+            ElemAlignContent::End => taffy::AlignContent::End,
+            ElemAlignContent::FlexStart => taffy::AlignContent::FlexStart,
+            ElemAlignContent::FlexEnd => taffy::AlignContent::FlexEnd,
+            ElemAlignContent::Center => taffy::AlignContent::Center,
+            ElemAlignContent::SpaceBetween => taffy::AlignContent::SpaceBetween,
+            ElemAlignContent::SpaceAround => taffy::AlignContent::SpaceAround,
+            ElemAlignContent::SpaceEvenly => taffy::AlignContent::SpaceEvenly,
         }),
         gap: Size {
-            width: length(style.gap.unwrap_or(0.0)),
-            height: length(style.gap.unwrap_or(0.0)),
+            width: lengths.gap(&style.column_gap.or(style.gap), LengthField::ColumnGap),
+            height: lengths.gap(&style.row_gap.or(style.gap), LengthField::RowGap),
         },
         flex_wrap: match style.flex_wrap {
             Some(ElemFlexWrap::Wrap) => taffy::FlexWrap::Wrap,
-            _ => taffy::FlexWrap::NoWrap,
+            Some(ElemFlexWrap::WrapReverse) => taffy::FlexWrap::WrapReverse,
+            Some(ElemFlexWrap::Nowrap) | None => taffy::FlexWrap::NoWrap,
         },
-        ..Default::default()
+        grid_row: grid_line_to_taffy(&style.grid_row),
+        grid_column: grid_line_to_taffy(&style.grid_column),
+        ..flex_item_to_taffy(&style.flex_item, lengths)
     }
 }
 
-fn text_style_to_taffy(style: &TextStyle) -> Style {
+fn grid_style_to_taffy(style: &GridStyle, lengths: &mut Lengths) -> Style {
+    let (position, inset) = position_to_taffy(
+        style.position,
+        [&style.top, &style.right, &style.bottom, &style.left],
+        lengths,
+    );
     Style {
-        flex_grow: style.flex_grow.unwrap_or(0.0),
-        flex_shrink: style.flex_shrink.unwrap_or(1.0),
-        ..Default::default()
+        position,
+        inset,
+        overflow: overflow_to_taffy(style.overflow),
+        display: match style.display {
+            Some(element::Display::None) => taffy::Display::None,
+            _ => taffy::Display::Grid,
+        },
+        size: Size {
+            width: lengths.dimension(&style.width, LengthField::Width),
+            height: lengths.dimension(&style.height, LengthField::Height),
+        },
+        min_size: Size {
+            width: lengths.dimension(&style.min_width, LengthField::MinWidth),
+            height: lengths.dimension(&style.min_height, LengthField::MinHeight),
+        },
+        max_size: Size {
+            width: lengths.dimension(&style.max_width, LengthField::MaxWidth),
+            height: lengths.dimension(&style.max_height, LengthField::MaxHeight),
+        },
+        margin: lengths.margin(&style.margin),
+        padding: lengths.padding(&style.padding),
+        border: border_to_taffy(&resolve_borders(
+            style.border_width,
+            &style.border_color,
+            style.border_style,
+            [&style.border_top, &style.border_right, &style.border_bottom, &style.border_left],
+        )),
+        grid_template_columns: grid_template_to_taffy(&style.grid_template_columns),
+        grid_template_rows: grid_template_to_taffy(&style.grid_template_rows),
+        grid_auto_flow: match style.grid_auto_flow {
+            Some(ElemGridAutoFlow::Row) | None => taffy::GridAutoFlow::Row,
+            Some(ElemGridAutoFlow::Column) => taffy::GridAutoFlow::Column,
+            Some(ElemGridAutoFlow::RowDense) => taffy::GridAutoFlow::RowDense,
+            Some(ElemGridAutoFlow::ColumnDense) => taffy::GridAutoFlow::ColumnDense,
+        },
+        gap: Size {
+            width: lengths.gap(&style.column_gap.or(style.gap), LengthField::ColumnGap),
+            height: lengths.gap(&style.row_gap.or(style.gap), LengthField::RowGap),
+        },
+        grid_row: grid_line_to_taffy(&style.grid_row),
+        grid_column: grid_line_to_taffy(&style.grid_column),
+        ..flex_item_to_taffy(&style.flex_item, lengths)
     }
 }
 
-fn image_style_to_taffy(style: &ImageStyle, intrinsic_width: f32, intrinsic_height: f32) -> Style {
+fn text_style_to_taffy(style: &TextStyle, lengths: &mut Lengths) -> Style {
+    let (position, inset) = position_to_taffy(
+        style.position,
+        [&style.top, &style.right, &style.bottom, &style.left],
+        lengths,
+    );
     Style {
+        position,
+        inset,
+        margin: lengths.margin(&style.margin),
+        grid_row: grid_line_to_taffy(&style.grid_row),
+        grid_column: grid_line_to_taffy(&style.grid_column),
+        ..flex_item_to_taffy(&style.flex_item, lengths)
+    }
+}
+
+fn image_style_to_taffy(
+    style: &ImageStyle,
+    intrinsic_width: f32,
+    intrinsic_height: f32,
+    lengths: &mut Lengths,
+) -> Style {
+    let (position, inset) = position_to_taffy(
+        style.position,
+        [&style.top, &style.right, &style.bottom, &style.left],
+        lengths,
+    );
+    Style {
+        position,
+        inset,
         size: Size {
-            width: style
-                .width
-                .as_ref()
-                .map(|d| dimension_to_taffy(&Some(d.clone())))
-                .unwrap_or(length(intrinsic_width)),
-            height: style
-                .height
-                .as_ref()
-                .map(|d| dimension_to_taffy(&Some(d.clone())))
-                .unwrap_or(length(intrinsic_height)),
+            width: match style.width {
+                Some(_) => lengths.dimension(&style.width, LengthField::Width),
+                None => length(intrinsic_width),
+            },
+            height: match style.height {
+                Some(_) => lengths.dimension(&style.height, LengthField::Height),
+                None => length(intrinsic_height),
+            },
         },
         min_size: Size {
-            width: dimension_to_taffy(&style.min_width),
-            height: dimension_to_taffy(&style.min_height),
+            width: lengths.dimension(&style.min_width, LengthField::MinWidth),
+            height: lengths.dimension(&style.min_height, LengthField::MinHeight),
         },
         max_size: Size {
-            width: dimension_to_taffy(&style.max_width),
-            height: dimension_to_taffy(&style.max_height),
+            width: lengths.dimension(&style.max_width, LengthField::MaxWidth),
+            height: lengths.dimension(&style.max_height, LengthField::MaxHeight),
         },
-        margin: spacing_to_taffy_rect(&style.margin),
-        flex_grow: style.flex_grow.unwrap_or(0.0),
-        flex_shrink: style.flex_shrink.unwrap_or(1.0),
+        margin: lengths.margin(&style.margin),
+        grid_row: grid_line_to_taffy(&style.grid_row),
+        grid_column: grid_line_to_taffy(&style.grid_column),
+        ..flex_item_to_taffy(&style.flex_item, lengths)
+    }
+}
+
+/// An element's flex item properties, with the rest of the style left at
+/// its defaults.
+fn flex_item_to_taffy(item: &FlexItemStyle, lengths: &mut Lengths) -> Style {
+    Style {
+        flex_grow: item.flex_grow.unwrap_or(0.0),
+        flex_shrink: item.flex_shrink.unwrap_or(1.0),
+        flex_basis: lengths.flex_basis(&item.flex_basis),
+        align_self: item.align_self.map(align_items_to_taffy),
         ..Default::default()
     }
 }
 
-fn dimension_to_taffy(dim: &Option<Dimension>) -> taffy::Dimension {
-    match dim {
-        None => taffy::Dimension::auto(),
-        Some(Dimension::Px(px)) => taffy::Dimension::length(*px),
-        Some(Dimension::Percent(s)) => {
-            let pct = s.trim_end_matches('%').parse::<f32>().unwrap_or(0.0);
-            taffy::Dimension::percent(pct / 100.0)
-        }
+fn align_items_to_taffy(align: ElemAlignItems) -> taffy::AlignItems {
+    match align {
+        ElemAlignItems::FlexStart => taffy::AlignItems::FlexStart,
+        ElemAlignItems::FlexEnd => taffy::AlignItems::FlexEnd,
+        ElemAlignItems::Center => taffy::AlignItems::Center,
+        ElemAlignItems::Stretch => taffy::AlignItems::Stretch,
+        ElemAlignItems::Baseline => taffy::AlignItems::Baseline,
     }
 }
 
-fn spacing_to_taffy_rect(spacing: &Option<Spacing>) -> Rect<LengthPercentageAuto> {
-    match spacing {
-        None => Rect {
-            top: LengthPercentageAuto::length(0.0),
-            right: LengthPercentageAuto::length(0.0),
-            bottom: LengthPercentageAuto::length(0.0),
-            left: LengthPercentageAuto::length(0.0),
-        },
-        Some(s) => {
-            let [top, right, bottom, left] = s.to_edges();
+fn overflow_to_taffy(overflow: Option<Overflow>) -> taffy::Point<taffy::Overflow> {
+    let overflow = match overflow {
+        Some(Overflow::Hidden) => taffy::Overflow::Hidden,
+        Some(Overflow::Visible) | None => taffy::Overflow::Visible,
+    };
+    taffy::Point {
+        x: overflow,
+        y: overflow,
+    }
+}
+
+fn is_positioned(position: Option<ElemPosition>) -> bool {
+    matches!(
+        position,
+        Some(ElemPosition::Relative) | Some(ElemPosition::Absolute)
+    )
+}
+
+/// Map `position` and `top`/`right`/`bottom`/`left` to Taffy. Insets are
+/// ignored for statically positioned elements, as in CSS.
+fn position_to_taffy(
+    position: Option<ElemPosition>,
+    [top, right, bottom, left]: [&Option<Dimension>; 4],
+    lengths: &mut Lengths,
+) -> (taffy::Position, Rect<LengthPercentageAuto>) {
+    let mut inset = |dim: &Option<Dimension>, side: usize| match dim {
+        None => LengthPercentageAuto::auto(),
+        Some(dim) => lengths.length(dim, LengthField::Inset(side)).into(),
+    };
+    match position {
+        Some(ElemPosition::Absolute) => (
+            taffy::Position::Absolute,
             Rect {
-                top: LengthPercentageAuto::length(top),
-                right: LengthPercentageAuto::length(right),
-                bottom: LengthPercentageAuto::length(bottom),
-                left: LengthPercentageAuto::length(left),
-            }
-        }
+                top: inset(top, 0),
+                right: inset(right, 1),
+                bottom: inset(bottom, 2),
+                left: inset(left, 3),
+            },
+        ),
+        Some(ElemPosition::Relative) => (
+            taffy::Position::Relative,
+            Rect {
+                top: inset(top, 0),
+                right: inset(right, 1),
+                bottom: inset(bottom, 2),
+                left: inset(left, 3),
+            },
+        ),
+        Some(ElemPosition::Static) | None => (taffy::Position::Relative, Rect::auto()),
     }
 }
 
-fn spacing_to_taffy_rect_lp(spacing: &Option<Spacing>) -> Rect<LengthPercentage> {
-    match spacing {
-        None => Rect {
-            top: LengthPercentage::length(0.0),
-            right: LengthPercentage::length(0.0),
-            bottom: LengthPercentage::length(0.0),
-            left: LengthPercentage::length(0.0),
+fn grid_template_to_taffy(template: &Option<GridTemplate>) -> Vec<GridTemplateComponent<String>> {
+    let Some(template) = template else {
+        return Vec::new();
+    };
+    template
+        .0
+        .iter()
+        .map(|track| match track {
+            GridTrack::Single(size) => GridTemplateComponent::Single(track_size_to_taffy(size)),
+            GridTrack::Repeat(count, sizes) => repeat(
+                match count {
+                    RepeatCount::Count(n) => RepetitionCount::Count(*n),
+                    RepeatCount::AutoFill => RepetitionCount::AutoFill,
+                    RepeatCount::AutoFit => RepetitionCount::AutoFit,
+                },
+                sizes.iter().map(track_size_to_taffy).collect(),
+            ),
+        })
+        .collect()
+}
+
+fn track_size_to_taffy(size: &TrackSize) -> TrackSizingFunction {
+    let min = match size.min {
+        TrackBreadth::Px(px) => MinTrackSizingFunction::length(px),
+        TrackBreadth::Percent(pct) => MinTrackSizingFunction::percent(pct / 100.0),
+        TrackBreadth::Auto | TrackBreadth::Fr(_) => MinTrackSizingFunction::auto(),
+        TrackBreadth::MinContent => MinTrackSizingFunction::min_content(),
+        TrackBreadth::MaxContent => MinTrackSizingFunction::max_content(),
+    };
+    let max = match size.max {
+        TrackBreadth::Px(px) => MaxTrackSizingFunction::length(px),
+        TrackBreadth::Percent(pct) => MaxTrackSizingFunction::percent(pct / 100.0),
+        TrackBreadth::Fr(fr) => MaxTrackSizingFunction::fr(fr),
+        TrackBreadth::Auto => MaxTrackSizingFunction::auto(),
+        TrackBreadth::MinContent => MaxTrackSizingFunction::min_content(),
+        TrackBreadth::MaxContent => MaxTrackSizingFunction::max_content(),
+    };
+    minmax(min, max)
+}
+
+fn grid_line_to_taffy(line: &Option<GridLine>) -> Line<GridPlacement> {
+    let placement = |p: ElemGridPlacement| match p {
+        ElemGridPlacement::Auto => GridPlacement::Auto,
+        ElemGridPlacement::Line(n) => taffy::style_helpers::line(n),
+        ElemGridPlacement::Span(n) => GridPlacement::Span(n),
+    };
+    match line {
+        None => Line {
+            start: GridPlacement::Auto,
+            end: GridPlacement::Auto,
         },
-        Some(s) => {
-            let [top, right, bottom, left] = s.to_edges();
-            Rect {
-                top: LengthPercentage::length(top),
-                right: LengthPercentage::length(right),
-                bottom: LengthPercentage::length(bottom),
-                left: LengthPercentage::length(left),
-            }
+        Some(l) => Line {
+            start: placement(l.start),
+            end: placement(l.end),
+        },
+    }
+}
+
+/// Tree level at which a `calc()` of `field` on `node_id` is resolved: the
+/// depth of the box its percentage refers to, so that box's own `calc()`
+/// lengths are resolved first.
+fn calc_level(taffy: &TaffyTree<NodeContext>, node_id: NodeId, field: LengthField) -> usize {
+    let depth = std::iter::successors(taffy.parent(node_id), |&node| taffy.parent(node)).count();
+    match field {
+        // Gaps are percentages of the box itself
+        LengthField::ColumnGap | LengthField::RowGap => depth + 1,
+        _ => depth,
+    }
+}
+
+/// Size that percentages of `field` on `node_id` refer to: the parent's
+/// content box (its padding box for absolutely positioned boxes), or the
+/// box's own content box for gaps.
+fn percent_basis(
+    taffy: &TaffyTree<NodeContext>,
+    node_id: NodeId,
+    field: LengthField,
+    containing_block: Size<f32>,
+) -> Result<f32, LayoutError> {
+    let content_box = |layout: &Layout, include_padding: bool| {
+        let mut width = layout.size.width - layout.border.left - layout.border.right;
+        let mut height = layout.size.height - layout.border.top - layout.border.bottom;
+        if !include_padding {
+            width -= layout.padding.left + layout.padding.right;
+            height -= layout.padding.top + layout.padding.bottom;
         }
+        Size { width, height }
+    };
+    let size = match (field, taffy.parent(node_id)) {
+        (LengthField::ColumnGap | LengthField::RowGap, _) => content_box(taffy.layout(node_id)?, false),
+        (_, Some(parent)) => {
+            let absolute = taffy.style(node_id)?.position == taffy::Position::Absolute;
+            content_box(taffy.layout(parent)?, absolute)
+        }
+        (_, None) => containing_block,
+    };
+    let horizontal = match (field, taffy.parent(node_id)) {
+        // Percentages of the flex container's main size
+        (LengthField::FlexBasis, Some(parent)) => matches!(
+            taffy.style(parent)?.flex_direction,
+            taffy::FlexDirection::Row | taffy::FlexDirection::RowReverse
+        ),
+        _ => field.is_horizontal(),
+    };
+    Ok(if horizontal { size.width } else { size.height })
+}
+
+/// Resolve the four border sides of a container.
+///
+/// A side without a width falls back to `borderWidth`, or to CSS's `medium`
+/// (3px) when the side is set but no width is given anywhere.
+fn resolve_borders(
+    width: Option<f32>,
+    color: &Option<Color>,
+    style: Option<BorderStyle>,
+    sides: [&Option<Border>; 4],
+) -> [BorderSide; 4] {
+    sides.map(|side| match side {
+        Some(side) => BorderSide {
+            width: side.width.or(width).unwrap_or(3.0),
+            style: side.style.or(style).unwrap_or_default(),
+            color: side.color.clone().or_else(|| color.clone()),
+        },
+        None => BorderSide {
+            width: width.unwrap_or(0.0),
+            style: style.unwrap_or_default(),
+            color: color.clone(),
+        },
+    })
+}
+
+fn border_to_taffy(sides: &[BorderSide; 4]) -> Rect<LengthPercentage> {
+    let [top, right, bottom, left] = sides;
+    Rect {
+        top: LengthPercentage::length(top.width),
+        right: LengthPercentage::length(right.width),
+        bottom: LengthPercentage::length(bottom.width),
+        left: LengthPercentage::length(left.width),
     }
 }
 
@@ -19,18 +19,26 @@
 //! println!("{}", result.svg);
 //! ```
 
+mod color;
 pub mod element;
 pub mod layout;
 pub mod render;
+pub mod subset;
 pub mod svg;
 pub mod text;
 
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use parley::fontique::Blob;
 use serde::{Deserialize, Serialize};
 
+use element::{FontStretch, FontStyle};
+
 pub use element::Element;
 pub use layout::LayoutEngine;
 pub use render::RenderTree;
 pub use svg::SvgOptions;
+pub use text::FontRun;
 
 /// A font source to register before rendering.
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -44,6 +52,12 @@ pub struct FontSource {
     /// Font weight (default: 400).
     #[serde(default = "default_font_weight")]
     pub weight: u16,
+    /// Font style (default: normal).
+    #[serde(default)]
+    pub style: FontStyle,
+    /// Font stretch (default: normal).
+    #[serde(default)]
+    pub stretch: FontStretch,
     /// Base64-encoded font data (TTF/OTF/WOFF2) — used for text layout.
     #[serde(default)]
     pub data: Option<String>,
@@ -61,15 +75,27 @@ pub struct CompileOptions {
     pub width: f32,
     /// Output height in pixels (auto-computed if not specified)
     pub height: Option<f32>,
-    /// Default font size (default: 16)
+    /// Root font size: the size of `rem` lengths and the `fontSize` inherited
+    /// by text without one (default: 16)
     #[serde(default = "default_font_size")]
     pub font_size: f32,
-    /// Font family applied to text elements without an explicit fontFamily
+    /// Root font family, inherited by text without a fontFamily
     #[serde(default)]
     pub font_family: Option<String>,
+    /// Font families tried, in order, for characters the fonts of a text's
+    /// own family stack have no glyphs for (e.g. CJK, emoji, symbols)
+    #[serde(default)]
+    pub font_fallbacks: Vec<String>,
     /// Fonts to register.
     #[serde(default)]
     pub fonts: Vec<FontSource>,
+    /// Embed fonts with `data` into the SVG as subsetted data-URI @font-face
+    #[serde(default)]
+    pub embed_fonts: bool,
+    /// Position every character of `<text>` output at its shaped glyph
+    /// position, so viewers don't re-shape lines (larger output)
+    #[serde(default)]
+    pub glyph_positions: bool,
 }
 
 fn default_font_size() -> f32 {
@@ -83,7 +109,10 @@ impl Default for CompileOptions {
             height: None,
             font_size: 16.0,
             font_family: None,
+            font_fallbacks: Vec::new(),
             fonts: Vec::new(),
+            embed_fonts: false,
+            glyph_positions: false,
         }
     }
 }
@@ -98,6 +127,8 @@ pub struct CompileResult {
     pub width: f32,
     /// Computed height
     pub height: f32,
+    /// Text runs with the font each resolved to
+    pub font_runs: Vec<FontRun>,
     /// Any warnings during compilation
     pub warnings: Vec<String>,
 }
@@ -171,27 +202,35 @@ pub fn compile_element(
     element: &Element,
     options: &CompileOptions,
 ) -> Result<CompileResult, CompileError> {
-    let warnings = Vec::new();
+    let mut warnings = Vec::new();
 
     // Create layout engine
     let mut layout_engine = LayoutEngine::new();
+    layout_engine.font_fallbacks = options.font_fallbacks.clone();
+    layout_engine.font_size = options.font_size;
 
     // Decode and register base64 font data (used by CLI; WASM falls back to approximate layout)
+    let mut font_blobs = Vec::with_capacity(options.fonts.len());
     for font in &options.fonts {
-        if let Some(data) = &font.data {
-            use base64::Engine;
-            let bytes = base64::engine::general_purpose::STANDARD
-                .decode(data)
-                .map_err(|e| CompileError {
-                    message: format!("Invalid base64 font data: {}", e),
-                    kind: "font_error".to_string(),
-                })?;
-            layout_engine.text_engine.register_font(bytes);
-        }
+        let blob = match &font.data {
+            Some(data) => {
+                let bytes = BASE64
+                    .decode(data)
+                    .map_err(|e| CompileError {
+                        message: format!("Invalid base64 font data: {}", e),
+                        kind: "font_error".to_string(),
+                    })?;
+                let blob = Blob::from(bytes);
+                layout_engine.text_engine.register_font_source(blob.clone(), font);
+                Some(blob)
+            }
+            None => None,
+        };
+        font_blobs.push(blob);
     }
 
     // Compute layout
-    let layout_result = layout_engine
+    let mut layout_result = layout_engine
         .compute_layout(element, options.width, options.height, options.font_family.as_deref())
         .map_err(|e| CompileError {
             message: e.to_string(),
@@ -199,20 +238,94 @@ pub fn compile_element(
         })?;
 
     // Build render tree
-    let render_tree = render::build_render_tree(&layout_result, &mut layout_engine.text_engine);
+    let render_tree = render::build_render_tree(&mut layout_result, &mut layout_engine.text_engine);
+
+    let font_runs = layout_engine.text_engine.font_runs().to_vec();
+    for run in font_runs.iter().filter(|run| run.missing_glyphs) {
+        warnings.push(format!(
+            "Font '{}' is missing glyphs for \"{}\"",
+            run.font_family, run.text
+        ));
+    }
+
+    // Replace embedded font sources with subsetted data URIs
+    let fonts = if options.embed_fonts {
+        embed_fonts(
+            &options.fonts,
+            &font_blobs,
+            &render_tree,
+            &layout_engine.text_engine,
+            &mut warnings,
+        )
+    } else {
+        options.fonts.clone()
+    };
 
     // Generate SVG
-    let svg_options = SvgOptions::default();
-    let svg = svg::generate_svg(&render_tree, &svg_options, &options.fonts);
+    let svg_options = SvgOptions {
+        glyph_positions: options.glyph_positions,
+        ..SvgOptions::default()
+    };
+    let svg = svg::generate_svg(&render_tree, &svg_options, &fonts);
 
     Ok(CompileResult {
         svg,
         width: render_tree.width,
         height: render_tree.height,
+        font_runs,
         warnings,
     })
 }
 
+/// Turn fonts with `data` into @font-face sources pointing at a data URI of
+/// the font subsetted to the glyphs used by the document's text.
+fn embed_fonts(
+    fonts: &[FontSource],
+    blobs: &[Option<Blob<u8>>],
+    tree: &RenderTree,
+    text_engine: &text::TextLayoutEngine,
+    warnings: &mut Vec<String>,
+) -> Vec<FontSource> {
+    let text: String = tree
+        .commands
+        .iter()
+        .filter_map(|cmd| match cmd {
+            render::RenderCommand::Text { content, .. } => Some(content.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    fonts
+        .iter()
+        .zip(blobs)
+        .map(|(font, blob)| {
+            let Some(blob) = blob else {
+                return font.clone();
+            };
+            let bytes = match text_engine.used_glyphs(blob.id()) {
+                Some(glyphs) => subset::subset_font(blob.data(), &text, glyphs),
+                None => subset::subset_font(blob.data(), &text, &Default::default()),
+            };
+            let bytes = bytes.unwrap_or_else(|| {
+                warnings.push(format!(
+                    "Font '{}' could not be subset; embedding the full font",
+                    font.family
+                ));
+                blob.data().to_vec()
+            });
+            FontSource {
+                url: Some(format!(
+                    "data:{};base64,{}",
+                    subset::font_mime_type(&bytes),
+                    BASE64.encode(&bytes)
+                )),
+                data: None,
+                ..font.clone()
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -256,6 +369,725 @@ mod tests {
         assert!(result.svg.contains("#ff0000"));
     }
 
+    #[test]
+    fn test_compile_grid_element() {
+        let json = r###"{
+            "type": "grid",
+            "style": { "width": 300, "gridTemplateColumns": "100px 1fr", "gap": 10 },
+            "children": [
+                { "type": "box", "style": { "height": 50, "backgroundColor": "#ff0000" } },
+                { "type": "box", "style": { "height": 50, "backgroundColor": "#00ff00" } },
+                { "type": "box", "style": { "height": 20, "backgroundColor": "#0000ff", "gridColumn": "1 / span 2" } }
+            ]
+        }"###;
+
+        let result = compile(json, &CompileOptions::default()).unwrap();
+        assert!(result.svg.contains(r##"<rect x="0.00" y="0.00" width="100.00" height="50.00" fill="#ff0000"/>"##));
+        assert!(result.svg.contains(r##"<rect x="110.00" y="0.00" width="190.00" height="50.00" fill="#00ff00"/>"##));
+        assert!(result.svg.contains(r##"<rect x="0.00" y="60.00" width="300.00" height="20.00" fill="#0000ff"/>"##));
+        assert_eq!(result.height, 80.0);
+    }
+
+    #[test]
+    fn test_compile_absolute_z_index() {
+        let json = r###"{
+            "type": "box",
+            "style": { "width": 100, "height": 100 },
+            "children": [
+                { "type": "box", "style": { "position": "absolute", "top": 5, "right": 5, "width": 20, "height": 20, "zIndex": 1, "backgroundColor": "#ff0000" } },
+                { "type": "box", "style": { "width": 100, "height": 100, "backgroundColor": "#0000ff" } }
+            ]
+        }"###;
+
+        let result = compile(json, &CompileOptions::default()).unwrap();
+        let badge = result.svg.find(r##"<rect x="75.00" y="5.00" width="20.00" height="20.00" fill="#ff0000"/>"##).unwrap();
+        let avatar = result.svg.find("#0000ff").unwrap();
+        assert!(avatar < badge);
+    }
+
+    #[test]
+    fn test_compile_overflow_hidden() {
+        let json = r###"{
+            "type": "flex",
+            "style": { "width": 100, "height": 100, "borderRadius": 12, "borderWidth": 2, "borderColor": "#000000", "overflow": "hidden" },
+            "children": [
+                { "type": "box", "style": { "width": 100, "height": 100, "backgroundColor": "#ff0000" } }
+            ]
+        }"###;
+
+        let result = compile(json, &CompileOptions::default()).unwrap();
+        assert!(result.svg.contains(
+            r##"<clipPath id="clip-0"><rect x="2.00" y="2.00" width="96.00" height="96.00" rx="10.00"/></clipPath>"##
+        ));
+        let clip = result.svg.find(r##"<g clip-path="url(#clip-0)">"##).unwrap();
+        let child = result.svg.find("#ff0000").unwrap();
+        assert!(clip < child);
+    }
+
+    #[test]
+    fn test_compile_gradient_background() {
+        let json = r###"{
+            "type": "flex",
+            "style": { "width": 200, "height": 100 },
+            "children": [
+                { "type": "box", "style": { "width": 100, "height": 100, "backgroundColor": "#ffffff", "backgroundImage": "linear-gradient(90deg, #ff0000, #0000ff 100%)" } },
+                { "type": "box", "style": { "width": 100, "height": 100, "backgroundImage": "radial-gradient(circle closest-side, #000000, transparent)" } }
+            ]
+        }"###;
+
+        let result = compile(json, &CompileOptions::default()).unwrap();
+        assert!(result.svg.contains(
+            r##"<linearGradient id="grad-0" gradientUnits="userSpaceOnUse" x1="0.00" y1="50.00" x2="100.00" y2="50.00">"##
+        ));
+        assert!(result.svg.contains(r##"cx="50.00" cy="50.00" r="50.00""##));
+        assert!(result.svg.contains(r##"transform="translate(100.00 0.00)" fill="url(#grad-1)""##));
+        assert!(result.svg.contains(r##"stop-opacity="0.000""##));
+        // The gradient paints over the background color
+        let background = result.svg.find(r##"fill="#ffffff""##).unwrap();
+        assert!(background < result.svg.find(r##"fill="url(#grad-0)""##).unwrap());
+
+        let invalid = r#"{ "type": "box", "style": { "backgroundImage": "linear-gradient(red)" } }"#;
+        assert!(compile(invalid, &CompileOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_compile_box_shadow() {
+        let json = r###"{
+            "type": "flex",
+            "style": { "width": 300, "padding": 20, "gap": 20 },
+            "children": [
+                { "type": "box", "style": { "width": 100, "height": 80, "borderRadius": 8, "backgroundColor": "#ffffff", "boxShadow": "0 4px 12px rgba(0,0,0,0.25), inset 0 0 0 2px #ff0000" } },
+                { "type": "image", "src": "a.png", "width": 100, "height": 80, "style": { "boxShadow": "0 4px 12px rgba(0,0,0,0.25)" } }
+            ]
+        }"###;
+
+        let result = compile(json, &CompileOptions::default()).unwrap();
+        let svg = &result.svg;
+        assert!(svg.contains(r#"<feGaussianBlur in="SourceAlpha" stdDeviation="6.00"/><feOffset dx="0.00" dy="4.00" result="offset"/>"#));
+        // Identical shadows on same-size elements share a filter
+        assert_eq!(svg.matches("<filter ").count(), 2);
+        assert_eq!(svg.matches(r##"filter="url(#shadow-0)""##).count(), 2);
+
+        // Outer shadow below the background, inset shadow above it
+        let background = svg.find(r##"fill="#ffffff""##).unwrap();
+        assert!(svg.find(r##"filter="url(#shadow-0)""##).unwrap() < background);
+        assert!(svg.find(r##"filter="url(#shadow-1)""##).unwrap() > background);
+    }
+
+    #[test]
+    fn test_compile_per_side_borders() {
+        let json = r###"{
+            "type": "flex",
+            "style": { "width": 100, "borderTop": "4px solid #ff0000", "borderBottom": "6px double #0000ff", "borderLeft": 2, "borderColor": "#000000" },
+            "children": [
+                { "type": "box", "style": { "height": 10 } },
+                { "type": "box", "style": { "width": 50, "height": 50, "borderWidth": 2, "borderColor": "#333333", "borderStyle": "dashed" } }
+            ]
+        }"###;
+
+        let result = compile(json, &CompileOptions::default()).unwrap();
+        // Taffy reserves the per-side widths
+        assert_eq!(result.height, 60.0);
+        assert!(result.svg.contains(r#"<polygon points="0.00,0.00 100.00,0.00 100.00,30.00 15.00,30.00"/>"#));
+        assert!(result.svg.contains(r##"fill="#0000ff" fill-rule="evenodd""##));
+        // Right has no width, so nothing is drawn for it
+        assert_eq!(result.svg.matches("<clipPath").count(), 3);
+        // Uniform borders stay a single stroked rect
+        assert!(result.svg.contains(r##"stroke="#333333" stroke-width="2.00" stroke-dasharray="6.00 6.00"/>"##));
+    }
+
+    #[test]
+    fn test_compile_transform() {
+        let json = r###"{
+            "type": "flex",
+            "style": { "width": 300, "padding": 20 },
+            "children": [
+                { "type": "box", "style": { "width": 100, "height": 40, "backgroundColor": "#ff0000", "transform": "rotate(90deg)", "transformOrigin": "left top" } },
+                { "type": "box", "style": { "width": 50, "height": 40, "backgroundColor": "#0000ff" } }
+            ]
+        }"###;
+
+        let result = compile(json, &CompileOptions::default()).unwrap();
+        assert!(result.svg.contains(
+            r##"<g transform="matrix(0.0000 1.0000 -1.0000 0.0000 40.00 0.00)"><rect x="20.00" y="20.00" width="100.00" height="40.00" fill="#ff0000"/></g>"##
+        ));
+        // Transforms don't affect layout
+        assert!(result.svg.contains(r##"<rect x="120.00" y="20.00" width="50.00" height="40.00" fill="#0000ff"/>"##));
+    }
+
+    #[test]
+    fn test_compile_text_truncation() {
+        let json = r###"{
+            "type": "flex",
+            "style": { "width": 200, "flexDirection": "column" },
+            "children": [
+                { "type": "text", "content": "The quick brown fox jumps over the lazy dog and keeps running far away", "style": { "maxLines": 2, "textOverflow": "ellipsis" } },
+                { "type": "text", "content": "The quick brown fox jumps over the lazy dog", "style": { "whiteSpace": "nowrap", "textOverflow": "ellipsis" } },
+                { "type": "text", "content": "The quick brown fox jumps over the lazy dog", "style": { "maxLines": 1 } }
+            ]
+        }"###;
+
+        let result = compile(json, &CompileOptions::default()).unwrap();
+        let svg = &result.svg;
+        assert_eq!(svg.matches("<tspan").count(), 2);
+        assert_eq!(svg.matches('\u{2026}').count(), 2);
+        assert!(!svg.contains("far away"));
+        assert!(svg.contains(">The quick brown fox</text>"));
+        // Four visible lines in total
+        assert!(result.height < 16.0 * 1.2 * 5.0);
+
+        // A right-to-left word in left-to-right text is cut in logical order
+        let content = "Read: שלום עולם זה משפט ארוך מאוד";
+        let json = format!(
+            r#"{{ "type": "text", "content": "{content}", "style": {{ "whiteSpace": "nowrap", "textOverflow": "ellipsis" }} }}"#
+        );
+        let options = CompileOptions {
+            width: 200.0,
+            glyph_positions: true,
+            ..CompileOptions::default()
+        };
+        let svg = compile(&json, &options).unwrap().svg;
+        let x = svg.split("<text x=\"").nth(1).unwrap();
+        let text = &x[x.find('>').unwrap() + 1..x.find("</text>").unwrap()];
+        let kept = text.strip_suffix('\u{2026}').unwrap_or(text);
+        assert!(kept.len() < content.len() && content.starts_with(kept), "{text}");
+        let x: Vec<f32> = x[..x.find('"').unwrap()].split(' ').map(|x| x.parse().unwrap()).collect();
+        assert!(x.iter().all(|&x| (0.0..=200.0).contains(&x)), "{x:?}");
+    }
+
+    #[test]
+    fn test_compile_text_decoration() {
+        let json = r###"{
+            "type": "flex",
+            "style": { "width": 200, "flexDirection": "column" },
+            "children": [
+                { "type": "text", "content": "The quick brown fox jumps over the lazy dog", "style": { "color": "#333333", "textDecorationLine": "underline line-through" } },
+                { "type": "text", "content": "Vector", "style": { "textRendering": "vector", "textDecorationLine": "overline", "textDecorationStyle": "dashed", "textDecorationColor": "red", "textDecorationThickness": 2 } }
+            ]
+        }"###;
+
+        let result = compile(json, &CompileOptions::default()).unwrap();
+        let svg = &result.svg;
+        // Two wrapped lines, each underlined and struck through
+        assert_eq!(svg.matches(r##"stroke="#333333""##).count(), 4);
+        // Underlines paint before the text, line-throughs after it
+        let text = svg.find("<text").unwrap();
+        assert_eq!(svg[..text].matches("<line").count(), 2);
+        assert!(svg.contains(r##"stroke="#ff0000" stroke-width="2.00" stroke-dasharray="6.00 6.00"/>"##));
+    }
+
+    #[test]
+    fn test_compile_font_size_fit() {
+        let json = r###"{
+            "type": "flex",
+            "style": { "width": 300, "flexDirection": "column" },
+            "children": [
+                { "type": "flex", "style": { "height": 60 }, "children": [
+                    { "type": "text", "content": "Big headline", "style": { "flexGrow": 1, "fontSizeMode": "fit", "maxFontSize": 200 } }
+                ] },
+                { "type": "text", "content": "The quick brown fox jumps over the lazy dog", "style": { "fontSize": 40, "fontSizeMode": "fit", "maxLines": 1 } }
+            ]
+        }"###;
+
+        let result = compile(json, &CompileOptions::default()).unwrap();
+        let sizes: Vec<f32> = result
+            .svg
+            .split("font-size=\"")
+            .skip(1)
+            .map(|s| s[..s.find('"').unwrap()].parse().unwrap())
+            .collect();
+        assert_eq!(sizes.len(), 2);
+        // Grows until a single line fills the 60px height
+        assert!(sizes[0] > 16.0 && sizes[0] * 1.2 <= 60.5);
+        // Shrinks below fontSize to stay on one line
+        assert!(sizes[1] < 40.0);
+        assert!(!result.svg.contains("<tspan"));
+
+        // A zero base size doesn't scale spans to infinity
+        let json = r###"{
+            "type": "text",
+            "style": { "fontSize": 0, "fontSizeMode": "fit", "maxFontSize": 20 },
+            "spans": [{ "content": "Zero" }, { "content": " base", "style": { "fontSize": 10 } }]
+        }"###;
+        let result = compile(json, &CompileOptions::default()).unwrap();
+        assert!(!result.svg.contains("NaN") && !result.svg.contains("inf"));
+    }
+
+    #[test]
+    fn test_compile_image_object_fit() {
+        let json = r###"{
+            "type": "flex",
+            "children": [
+                { "type": "image", "src": "a.png", "width": 200, "height": 100, "style": { "width": 100, "height": 100, "objectFit": "cover", "objectPosition": "left" } },
+                { "type": "image", "src": "b.png", "width": 200, "height": 100, "style": { "width": 100, "height": 100, "objectFit": "fill" } }
+            ]
+        }"###;
+
+        let result = compile(json, &CompileOptions::default()).unwrap();
+        assert!(result.svg.contains(r#"preserveAspectRatio="xMinYMid slice" xlink:href="a.png" clip-path="url(#img-clip-0)"/>"#));
+        assert!(result.svg.contains(r#"preserveAspectRatio="none" xlink:href="b.png"/>"#));
+    }
+
+    #[test]
+    fn test_compile_font_style_and_stretch() {
+        let doc = r###"{
+            "meta": {
+                "width": 300,
+                "fonts": [{ "family": "Inter", "url": "inter-italic.woff2", "style": "italic", "stretch": "75%" }]
+            },
+            "content": {
+                "type": "text",
+                "style": { "fontFamily": "Inter", "fontStyle": "italic", "fontStretch": "condensed" },
+                "spans": [
+                    { "content": "Slanted " },
+                    { "content": "upright", "style": { "fontStyle": "normal", "fontStretch": 100 } }
+                ]
+            }
+        }"###;
+
+        let result = compile_document(doc).unwrap();
+        let svg = &result.svg;
+        assert!(svg.contains("src: url('inter-italic.woff2'); font-weight: 400; font-style: italic; font-stretch: condensed;"));
+        assert!(svg.contains(r#"font-weight="400" font-style="italic" font-stretch="condensed">Slanted "#));
+        assert!(svg.contains(r#"<tspan font-style="normal" font-stretch="normal">upright</tspan>"#));
+    }
+
+    #[test]
+    fn test_compile_letter_and_word_spacing() {
+        let plain = r###"{ "type": "text", "content": "NEW RELEASE", "style": { "fontSize": 12 } }"###;
+        let spaced = r###"{ "type": "text", "content": "NEW RELEASE", "style": { "fontSize": 12, "letterSpacing": 3, "wordSpacing": 4 } }"###;
+
+        let plain = compile(plain, &CompileOptions::default()).unwrap();
+        let spaced = compile(spaced, &CompileOptions::default()).unwrap();
+        assert!(spaced.svg.contains(r#"letter-spacing="3.00" word-spacing="4.00">NEW RELEASE</text>"#));
+        assert!(!plain.svg.contains("letter-spacing"));
+        // Eleven characters and one space wider
+        assert!((spaced.width - plain.width - (11.0 * 3.0 + 4.0)).abs() <= 1.0);
+    }
+
+    #[test]
+    fn test_measure_uses_text_style() {
+        let size = |style: &str| {
+            let json = format!(r#"{{ "type": "text", "content": "Heading", "style": {} }}"#, style);
+            let result = compile(&json, &CompileOptions::default()).unwrap();
+            (result.width, result.height)
+        };
+
+        let (regular_width, regular_height) = size(r#"{ "fontSize": 20 }"#);
+        let (bold_width, _) = size(r#"{ "fontSize": 20, "fontWeight": 700 }"#);
+        let (_, tall_height) = size(r#"{ "fontSize": 20, "lineHeight": 2 }"#);
+        assert!(bold_width > regular_width);
+        assert_eq!(regular_height, 24.0);
+        assert_eq!(tall_height, 40.0);
+    }
+
+    #[test]
+    fn test_text_layout_cached_for_render() {
+        let element: Element = serde_json::from_str(
+            r#"{ "type": "text", "content": "The quick brown fox jumps over the lazy dog", "style": { "textAlign": "center" } }"#,
+        )
+        .unwrap();
+        let mut engine = LayoutEngine::new();
+        let mut layout = engine.compute_layout(&element, 200.0, None, None).unwrap();
+
+        // The text shrinks below the width it was measured at, but its lines
+        // still fit, so they are only re-aligned
+        let width = layout.taffy.unrounded_layout(layout.root).size.width;
+        assert!(width < 200.0);
+        let Some(layout::NodeContext::Text(ctx)) = layout.taffy.get_node_context_mut(layout.root) else {
+            panic!("Expected text context");
+        };
+        let style = ctx.style.clone();
+        let shaped = ctx.cached_shape(width).expect("Expected cached shape");
+        let cached = engine.text_engine.layout_shaped(shaped, &style, Some(width));
+        let fresh = engine
+            .text_engine
+            .layout(&ctx.content, &ctx.style, &ctx.spans, Some(width));
+        assert_eq!(cached.lines.len(), fresh.lines.len());
+        for (a, b) in cached.lines.iter().zip(&fresh.lines) {
+            assert_eq!(a.text, b.text);
+            assert_eq!(a.decoration.x, b.decoration.x);
+        }
+        // Narrower than its lines, the text has to be broken again
+        assert!(ctx.cached_shape(width - 10.0).is_none());
+
+        // Fitted text is shaped at its final size for rendering
+        let element: Element = serde_json::from_str(
+            r#"{ "type": "text", "content": "Fitted", "style": { "fontSizeMode": "fit", "maxFontSize": 40 } }"#,
+        )
+        .unwrap();
+        let mut layout = engine.compute_layout(&element, 100.0, None, None).unwrap();
+        let width = layout.taffy.unrounded_layout(layout.root).size.width;
+        let Some(layout::NodeContext::Text(ctx)) = layout.taffy.get_node_context_mut(layout.root) else {
+            panic!("Expected text context");
+        };
+        assert!(ctx.style.font_size > 16.0);
+        assert!(ctx.cached_shape(width).is_some());
+    }
+
+    #[test]
+    fn test_compile_glyph_positions() {
+        let json = r###"{
+            "type": "flex",
+            "style": { "flexDirection": "column", "width": 200 },
+            "children": [
+                { "type": "text", "content": "Centered", "style": { "textAlign": "center" } }
+            ]
+        }"###;
+        let options = CompileOptions {
+            width: 200.0,
+            glyph_positions: true,
+            ..CompileOptions::default()
+        };
+
+        let result = compile(json, &options).unwrap();
+        let x = result.svg.split("<text x=\"").nth(1).unwrap();
+        let x: Vec<f32> = x[..x.find('"').unwrap()]
+            .split(' ')
+            .map(|x| x.parse().unwrap())
+            .collect();
+        assert_eq!(x.len(), "Centered".len());
+        assert!(x.windows(2).all(|w| w[0] < w[1]));
+        // Centered by layout rather than starting at the box edge
+        assert!(x[0] > 0.0);
+
+        let plain = compile(json, &CompileOptions { width: 200.0, ..CompileOptions::default() }).unwrap();
+        assert!(plain.svg.contains(&format!("<text x=\"{:.2}\" ", x[0])));
+    }
+
+    #[test]
+    fn test_compile_rtl_direction() {
+        let json = r###"{
+            "type": "flex",
+            "style": { "width": 300, "direction": "rtl" },
+            "children": [
+                { "type": "text", "content": "שלום" },
+                { "type": "text", "content": "Hello", "style": { "direction": "ltr" } }
+            ]
+        }"###;
+        let options = CompileOptions {
+            width: 300.0,
+            ..CompileOptions::default()
+        };
+
+        let result = compile(json, &options).unwrap();
+        let texts: Vec<&str> = result.svg.split("<text ").skip(1).collect();
+        assert_eq!(texts.len(), 2);
+        let x = |text: &str| text[3..text[3..].find('"').unwrap() + 3].parse::<f32>().unwrap();
+        // Inherited right-to-left text starts at the right edge of the row
+        assert!((x(texts[0]) - 300.0).abs() <= 1.0);
+        assert!(texts[0].contains(r#"direction="rtl">שלום<"#));
+        // The second item sits to its left and keeps its own direction
+        assert!(!texts[1].contains("direction="));
+        assert!(x(texts[1]) < x(texts[0]) - 20.0);
+
+        // Left-to-right content is shaped right-to-left by a direction mark,
+        // which isn't output
+        let json = r###"{ "type": "text", "content": "Hello", "style": { "fontFamily": "HTVG Test Sans", "direction": "rtl" } }"###;
+        let options = CompileOptions {
+            fonts: vec![font_source("HTVG Test Sans", TEST_FONT)],
+            glyph_positions: true,
+            ..CompileOptions::default()
+        };
+        let result = compile(json, &options).unwrap();
+        assert!(result.svg.contains(r#"direction="rtl">Hello</text>"#));
+        let x = result.svg.split("<text x=\"").nth(1).unwrap();
+        assert_eq!(x[..x.find('"').unwrap()].split(' ').count(), "Hello".len());
+        assert_eq!(result.font_runs[0].text, "Hello");
+
+        // Right-to-left text is cut from its logical end, and the rest stays
+        // inside the box
+        let content = "שלום עולם זה משפט ארוך מאוד בעברית שצריך לקצר";
+        let options = CompileOptions {
+            width: 150.0,
+            glyph_positions: true,
+            ..CompileOptions::default()
+        };
+        for limit in [r#""whiteSpace": "nowrap""#, r#""maxLines": 1"#] {
+            let json = format!(
+                r#"{{ "type": "text", "content": "{content}", "style": {{ "direction": "rtl", {limit}, "textOverflow": "ellipsis" }} }}"#
+            );
+            let svg = compile(&json, &options).unwrap().svg;
+            let x = svg.split("<text x=\"").nth(1).unwrap();
+            let text = &x[x.find('>').unwrap() + 1..x.find("</text>").unwrap()];
+            let kept = text.strip_suffix('\u{2026}').unwrap_or(text);
+            assert!(kept.len() < content.len() && content.starts_with(kept), "{text}");
+            let x: Vec<f32> = x[..x.find('"').unwrap()].split(' ').map(|x| x.parse().unwrap()).collect();
+            assert!(x.iter().all(|&x| x >= 0.0), "{x:?}");
+        }
+    }
+
+    #[test]
+    fn test_compile_font_fallbacks() {
+        let json = r###"{ "type": "text", "content": "Hi 山田", "style": { "fontFamily": "Inter, HTVG Test Sans" } }"###;
+        let options = CompileOptions {
+            fonts: vec![
+                font_source("HTVG Test Sans", TEST_FONT),
+                font_source("HTVG Test CJK", TEST_CJK_FONT),
+            ],
+            font_fallbacks: vec!["HTVG Test CJK".to_string(), "emoji".to_string()],
+            ..CompileOptions::default()
+        };
+
+        let result = compile(json, &options).unwrap();
+        assert!(result.svg.contains(
+            r#"font-family="'Inter', 'HTVG Test Sans', 'HTVG Test CJK', emoji""#
+        ));
+        // Characters the text's own fonts lack fall back per cluster, and
+        // each run reports the font it was shaped with
+        let runs: Vec<(&str, &str)> = result
+            .font_runs
+            .iter()
+            .map(|run| (run.text.as_str(), run.font_family.as_str()))
+            .collect();
+        assert_eq!(runs, [("Hi", "HTVG Test Sans"), ("山田", "HTVG Test CJK")]);
+        assert!(result.font_runs.iter().all(|run| !run.missing_glyphs));
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_compile_text_spans() {
+        let json = r###"{
+            "type": "text",
+            "style": { "color": "#333333" },
+            "spans": [
+                { "content": "Hello " },
+                { "content": "World", "style": { "fontWeight": 700, "color": "#0066cc" } }
+            ]
+        }"###;
+
+        let result = compile(json, &CompileOptions::default()).unwrap();
+        assert!(result.svg.contains("Hello <tspan font-weight=\"700\" fill=\"#0066cc\">World</tspan>"));
+    }
+
+    /// Small fonts checked in with the tests, so they don't depend on the
+    /// fonts installed on the machine: one for ASCII and one for a few CJK
+    /// characters.
+    const TEST_FONT: &[u8] = include_bytes!("../tests/fixtures/test-sans.ttf");
+    const TEST_CJK_FONT: &[u8] = include_bytes!("../tests/fixtures/test-cjk.ttf");
+
+    fn font_source(family: &str, data: &[u8]) -> FontSource {
+        FontSource {
+            family: family.to_string(),
+            url: None,
+            weight: 400,
+            style: FontStyle::Normal,
+            stretch: FontStretch::NORMAL,
+            data: Some(BASE64.encode(data)),
+        }
+    }
+
+    #[test]
+    fn test_compile_vector_text() {
+        let json = r###"{
+            "type": "flex",
+            "children": [
+                {
+                    "type": "text",
+                    "content": "Hello",
+                    "style": { "fontFamily": "HTVG Test Sans", "fontSize": 24, "color": "#333333", "textRendering": "vector" }
+                }
+            ]
+        }"###;
+        let options = CompileOptions {
+            fonts: vec![font_source("HTVG Test Sans", TEST_FONT)],
+            ..CompileOptions::default()
+        };
+
+        let result = compile(json, &options).unwrap();
+        assert!(result.svg.contains("<path d=\"M"));
+        assert!(result.svg.contains("fill=\"#333333\""));
+        assert!(!result.svg.contains("<text"));
+        assert_eq!(result.font_runs[0].font_family, "HTVG Test Sans");
+    }
+
+    #[test]
+    fn test_compile_embedded_font() {
+        let options = CompileOptions {
+            fonts: vec![font_source("HTVG Test Sans", TEST_FONT)],
+            embed_fonts: true,
+            ..CompileOptions::default()
+        };
+        let json = r###"{ "type": "text", "content": "Hi", "style": { "fontFamily": "HTVG Test Sans" } }"###;
+
+        let result = compile(json, &options).unwrap();
+        let start = result.svg.find("data:font/ttf;base64,").unwrap() + 21;
+        let end = start + result.svg[start..].find('\'').unwrap();
+        let subset = BASE64.decode(&result.svg[start..end]).unwrap();
+
+        assert!(result.warnings.is_empty());
+        assert!(subset.len() < TEST_FONT.len() / 4);
+        let face = ttf_parser::Face::parse(&subset, 0).unwrap();
+        let h = face.glyph_index('H').unwrap();
+        let z = face.glyph_index('z').unwrap();
+        assert!(face.outline_glyph(h, &mut NoopOutline).is_some());
+        assert!(face.outline_glyph(z, &mut NoopOutline).is_none());
+    }
+
+    struct NoopOutline;
+
+    impl ttf_parser::OutlineBuilder for NoopOutline {
+        fn move_to(&mut self, _: f32, _: f32) {}
+        fn line_to(&mut self, _: f32, _: f32) {}
+        fn quad_to(&mut self, _: f32, _: f32, _: f32, _: f32) {}
+        fn curve_to(&mut self, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32) {}
+        fn close(&mut self) {}
+    }
+
+    #[test]
+    fn test_compile_length_units() {
+        let json = r###"{
+            "type": "flex",
+            "style": { "width": 400, "padding": "1rem", "gap": "calc(10% - 26.8px)", "alignItems": "flex-start" },
+            "children": [
+                { "type": "box", "style": { "width": "calc(50% - 1em)", "height": 10, "backgroundColor": "#ff0000" } },
+                { "type": "box", "style": { "width": "10vw", "height": "2em", "borderRadius": "50%", "backgroundColor": "#0000ff" } }
+            ]
+        }"###;
+        let options = CompileOptions {
+            width: 400.0,
+            ..CompileOptions::default()
+        };
+
+        let result = compile(json, &options).unwrap();
+        assert!(result.svg.contains(r##"<rect x="16.00" y="16.00" width="168.00" height="10.00" fill="#ff0000"/>"##));
+        assert!(result.svg.contains(r##"<rect x="194.00" y="16.00" width="40.00" height="32.00" rx="16.00" fill="#0000ff"/>"##));
+
+        // A nested calc() resolves against its parent's resolved size
+        let json = r###"{
+            "type": "box",
+            "style": { "width": "calc(100% - 100px)" },
+            "children": [
+                { "type": "box", "style": { "width": "calc(50% + 1px)", "height": 10, "backgroundColor": "#ff0000" } }
+            ]
+        }"###;
+        let svg = compile(json, &options).unwrap().svg;
+        assert!(svg.contains(r##"<rect x="0.00" y="0.00" width="151.00" height="10.00" fill="#ff0000"/>"##));
+
+        let error = compile(r#"{ "type": "box", "style": { "margin": "10 oops" } }"#, &options).unwrap_err();
+        assert_eq!(error.kind, "parse_error");
+        assert!(error.message.contains("invalid margin: 10 oops"));
+        let error = compile(r#"{ "type": "box", "style": { "padding": "10 oops" } }"#, &options).unwrap_err();
+        assert_eq!(error.kind, "parse_error");
+        assert!(error.message.contains("invalid spacing: 10 oops"));
+    }
+
+    #[test]
+    fn test_compile_flex_items() {
+        let json = r###"{
+            "type": "flex",
+            "style": { "width": 300, "height": 40, "alignItems": "flex-start" },
+            "children": [
+                { "type": "flex", "style": { "flexGrow": 1, "height": 10, "backgroundColor": "#ff0000" } },
+                { "type": "box", "style": { "width": 50, "height": 10, "order": -1, "backgroundColor": "#0000ff" } },
+                { "type": "box", "style": { "width": 20, "height": 10, "alignSelf": "flex-end", "backgroundColor": "#00ff00" } }
+            ]
+        }"###;
+        let svg = compile(json, &CompileOptions::default()).unwrap().svg;
+        let blue = svg.find(r##"<rect x="0.00" y="0.00" width="50.00" height="10.00" fill="#0000ff"/>"##).unwrap();
+        let red = svg.find(r##"<rect x="50.00" y="0.00" width="230.00" height="10.00" fill="#ff0000"/>"##).unwrap();
+        assert!(blue < red, "order also sets paint order");
+        assert!(svg.contains(r##"<rect x="280.00" y="30.00" width="20.00" height="10.00" fill="#00ff00"/>"##));
+
+        let json = r###"{
+            "type": "flex",
+            "style": { "width": 200, "flexDirection": "column" },
+            "children": [
+                { "type": "box", "style": { "width": 20, "height": 10, "margin": "0 auto", "backgroundColor": "#ff0000" } },
+                { "type": "flex", "children": [
+                    { "type": "box", "style": { "flexBasis": "calc(50% - 10px)", "flexShrink": 0, "height": 10, "backgroundColor": "#0000ff" } }
+                ] }
+            ]
+        }"###;
+        let svg = compile(json, &CompileOptions::default()).unwrap().svg;
+        assert!(svg.contains(r##"<rect x="90.00" y="0.00" width="20.00" height="10.00" fill="#ff0000"/>"##));
+        assert!(svg.contains(r##"<rect x="0.00" y="10.00" width="90.00" height="10.00" fill="#0000ff"/>"##));
+    }
+
+    #[test]
+    fn test_compile_flex_wrap_lines() {
+        let json = r###"{
+            "type": "flex",
+            "style": {
+                "width": 100, "height": 100, "flexWrap": "wrap-reverse", "alignContent": "center",
+                "rowGap": 10, "columnGap": "5%", "justifyContent": "stretch"
+            },
+            "children": [
+                { "type": "box", "style": { "width": 40, "height": 20, "backgroundColor": "#ff0000" } },
+                { "type": "box", "style": { "width": 40, "height": 20, "backgroundColor": "#00ff00" } },
+                { "type": "box", "style": { "width": 40, "height": 20, "backgroundColor": "#0000ff" } }
+            ]
+        }"###;
+        let svg = compile(json, &CompileOptions::default()).unwrap().svg;
+        // Lines are centered as a block, the first one at the bottom
+        assert!(svg.contains(r##"<rect x="0.00" y="55.00" width="40.00" height="20.00" fill="#ff0000"/>"##));
+        assert!(svg.contains(r##"<rect x="45.00" y="55.00" width="40.00" height="20.00" fill="#00ff00"/>"##));
+        assert!(svg.contains(r##"<rect x="0.00" y="25.00" width="40.00" height="20.00" fill="#0000ff"/>"##));
+    }
+
+    #[test]
+    fn test_compile_current_color() {
+        let json = r###"{
+            "type": "text",
+            "content": "Hi",
+            "style": {
+                "color": "oklch(62.8% 0.2577 29.23)",
+                "textDecorationLine": "underline",
+                "textDecorationColor": "currentColor"
+            }
+        }"###;
+        let result = compile(json, &CompileOptions::default()).unwrap();
+        assert!(!result.svg.contains("#000000"));
+        assert!(result.svg.matches("#ff0000").count() >= 2);
+
+        // Containers resolve currentColor to the initial black text color
+        let json = r###"{
+            "type": "box",
+            "style": { "width": 10, "height": 10, "backgroundColor": "currentColor" }
+        }"###;
+        let result = compile(json, &CompileOptions::default()).unwrap();
+        assert!(result.svg.contains("fill=\"#000000\""));
+    }
+
+    #[test]
+    fn test_compile_inherited_text_style() {
+        let json = r###"{
+            "type": "flex",
+            "style": {
+                "width": 200,
+                "flexDirection": "column",
+                "color": "#336699",
+                "fontFamily": "Georgia, serif",
+                "fontSize": 20,
+                "fontWeight": 700,
+                "backgroundColor": "currentColor"
+            },
+            "children": [
+                { "type": "text", "content": "Inherited" },
+                {
+                    "type": "box",
+                    "style": { "color": "red", "fontSize": 10 },
+                    "children": [
+                        { "type": "text", "content": "Nested", "style": { "fontWeight": 400 } }
+                    ]
+                }
+            ]
+        }"###;
+        let result = compile(json, &CompileOptions::default()).unwrap();
+        assert!(result.svg.contains(r##"<rect x="0.00" y="0.00" width="200.00" height="36.00" fill="#336699"/>"##));
+        assert!(result.svg.contains(r##"fill="#336699" font-family="'Georgia', serif" font-size="20.00" font-weight="700">Inherited"##));
+        assert!(result.svg.contains(r##"fill="#ff0000" font-family="'Georgia', serif" font-size="10.00" font-weight="400">Nested"##));
+
+        // Options are the root values
+        let options = CompileOptions {
+            font_size: 24.0,
+            ..CompileOptions::default()
+        };
+        let json = r###"{ "type": "box", "children": [{ "type": "text", "content": "Root" }] }"###;
+        let result = compile(json, &options).unwrap();
+        assert!(result.svg.contains(r#"font-size="24.00""#));
+    }
+
     #[test]
     fn test_invalid_json() {
         let json = "not valid json";
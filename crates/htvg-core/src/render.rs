@@ -5,9 +5,12 @@
 
 use taffy::NodeId;
 
-use crate::element::Color;
-use crate::layout::{ElementType, LayoutResult};
-use crate::text::TextLayoutEngine;
+use crate::element::{
+    multiply_matrix, Color, Direction, FontStretch, FontStyle, Gradient, ObjectFit, ObjectPosition, Overflow, Shadow,
+    TextDecorationStyle, TextRendering,
+};
+use crate::layout::{BorderSide, ElementType, LayoutResult, NodeContext, TextStyleResolved};
+use crate::text::{clips_line, TextLayoutEngine, TextLayoutResult};
 
 /// A rectangle in pixel coordinates.
 #[derive(Debug, Clone, Copy)]
@@ -18,21 +21,34 @@ pub struct Rect {
     pub height: f32,
 }
 
+/// How a shape is filled.
+#[derive(Debug, Clone)]
+pub enum Paint {
+    Color(Color),
+    Gradient(Gradient),
+}
+
 /// Render commands that can be converted to SVG.
 #[derive(Debug, Clone)]
 pub enum RenderCommand {
+    /// Draw box shadows around (outer) or inside (inset) a rounded rectangle
+    BoxShadow {
+        rect: Rect,
+        border_radius: [f32; 4],
+        shadows: Vec<Shadow>,
+    },
+
     /// Draw a filled rectangle
     FillRect {
         rect: Rect,
-        color: Color,
+        paint: Paint,
         border_radius: [f32; 4],
     },
 
-    /// Draw a stroked rectangle (border)
-    StrokeRect {
+    /// Draw a border; sides are in top, right, bottom, left order
+    Border {
         rect: Rect,
-        color: Color,
-        width: f32,
+        sides: [BorderSide; 4],
         border_radius: [f32; 4],
     },
 
@@ -44,6 +60,11 @@ pub enum RenderCommand {
         font_family: String,
         font_size: f32,
         font_weight: u16,
+        font_style: FontStyle,
+        font_stretch: FontStretch,
+        letter_spacing: f32,
+        word_spacing: f32,
+        direction: Direction,
         color: Color,
         lines: Vec<TextLineRender>,
     },
@@ -54,11 +75,24 @@ pub enum RenderCommand {
         color: Color,
     },
 
+    /// Draw underlines, overlines or line-throughs
+    TextDecoration {
+        lines: Vec<DecorationLine>,
+        color: Color,
+        style: TextDecorationStyle,
+    },
+
     /// Draw an image
     Image {
         rect: Rect,
         src: String,
         border_radius: [f32; 4],
+        /// Intrinsic image width
+        image_width: f32,
+        /// Intrinsic image height
+        image_height: f32,
+        object_fit: ObjectFit,
+        object_position: ObjectPosition,
     },
 
     /// Begin a clipping region
@@ -70,6 +104,14 @@ pub enum RenderCommand {
     /// End clipping region
     PopClip,
 
+    /// Begin a group transformed by an SVG matrix `[a, b, c, d, e, f]`
+    PushTransform {
+        matrix: [f32; 6],
+    },
+
+    /// End transform group
+    PopTransform,
+
     /// Begin opacity group
     PushOpacity {
         opacity: f32,
@@ -85,6 +127,31 @@ pub struct TextLineRender {
     pub x: f32,
     pub y: f32,
     pub text: String,
+    /// Shaped x position of each character of `text` (empty if unknown).
+    pub char_x: Vec<f32>,
+    /// Styled runs making up the line (empty for unstyled text).
+    pub spans: Vec<TextSpanRender>,
+}
+
+/// A styled run of text within a line.
+#[derive(Debug, Clone)]
+pub struct TextSpanRender {
+    pub text: String,
+    pub font_family: String,
+    pub font_size: f32,
+    pub font_weight: u16,
+    pub font_style: FontStyle,
+    pub font_stretch: FontStretch,
+    pub color: Color,
+}
+
+/// A horizontal decoration line, centered on `y`.
+#[derive(Debug, Clone, Copy)]
+pub struct DecorationLine {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub thickness: f32,
 }
 
 /// The render tree - a flat list of commands in draw order.
@@ -95,9 +162,10 @@ pub struct RenderTree {
     pub height: f32,
 }
 
-/// Build render tree from layout result.
+/// Build render tree from layout result, aligning the text shaped during
+/// layout in place.
 pub fn build_render_tree(
-    layout: &LayoutResult,
+    layout: &mut LayoutResult,
     text_engine: &mut TextLayoutEngine,
 ) -> RenderTree {
     let mut commands = Vec::new();
@@ -109,7 +177,7 @@ pub fn build_render_tree(
 
     // Traverse tree in depth-first order
     render_node(
-        &layout,
+        layout,
         layout.root,
         0.0,
         0.0,
@@ -125,14 +193,14 @@ pub fn build_render_tree(
 }
 
 fn render_node(
-    layout: &LayoutResult,
+    layout: &mut LayoutResult,
     node_id: NodeId,
     parent_x: f32,
     parent_y: f32,
     commands: &mut Vec<RenderCommand>,
     text_engine: &mut TextLayoutEngine,
 ) {
-    let node_layout = layout.taffy.layout(node_id).unwrap();
+    let node_layout = *layout.taffy.layout(node_id).unwrap();
     let node_data = layout.node_data.get(&node_id);
 
     let x = parent_x + node_layout.location.x;
@@ -150,6 +218,20 @@ fn render_node(
     if let Some(data) = node_data {
         let visual = &data.visual;
 
+        // Transforms apply after layout, around the origin in the border box
+        let transform = visual.transform.as_ref().filter(|t| !t.0.is_empty()).map(|t| {
+            let origin_x = x + visual.transform_origin.x.resolve(width);
+            let origin_y = y + visual.transform_origin.y.resolve(height);
+            let matrix = multiply_matrix(
+                [1.0, 0.0, 0.0, 1.0, origin_x, origin_y],
+                t.to_matrix(width, height),
+            );
+            multiply_matrix(matrix, [1.0, 0.0, 0.0, 1.0, -origin_x, -origin_y])
+        });
+        if let Some(matrix) = transform {
+            commands.push(RenderCommand::PushTransform { matrix });
+        }
+
         // Handle opacity
         let needs_opacity = visual.opacity < 1.0;
         if needs_opacity {
@@ -158,93 +240,245 @@ fn render_node(
             });
         }
 
-        // Handle clipping for border-radius
-        let has_radius = visual.border_radius.iter().any(|&r| r > 0.0);
-        if has_radius && matches!(data.element_type, ElementType::Box | ElementType::Flex) {
-            // For now, skip clipping - just apply border-radius to the rect
+        // Outer shadows sit below the background
+        let (inset_shadows, outer_shadows): (Vec<Shadow>, Vec<Shadow>) =
+            visual.box_shadow.iter().cloned().partition(|s| s.inset);
+        if !outer_shadows.is_empty() {
+            commands.push(RenderCommand::BoxShadow {
+                rect,
+                border_radius: visual.border_radius,
+                shadows: outer_shadows,
+            });
         }
 
         // Draw background
-        if let Some(ref bg_color) = visual.background_color {
-            if bg_color.a > 0 {
-                commands.push(RenderCommand::FillRect {
-                    rect,
-                    color: bg_color.clone(),
-                    border_radius: visual.border_radius,
-                });
-            }
+        if let Some(ref bg_color) = visual.background_color
+            && bg_color.a > 0
+        {
+            commands.push(RenderCommand::FillRect {
+                rect,
+                paint: Paint::Color(bg_color.clone()),
+                border_radius: visual.border_radius,
+            });
+        }
+
+        // Gradients paint on top of the background color
+        if let Some(ref gradient) = visual.background_image {
+            commands.push(RenderCommand::FillRect {
+                rect,
+                paint: Paint::Gradient(gradient.clone()),
+                border_radius: visual.border_radius,
+            });
+        }
+
+        // Inset shadows paint over the background, inside the border
+        if !inset_shadows.is_empty() {
+            let (rect, border_radius) = padding_box(rect, node_layout.border, visual.border_radius);
+            commands.push(RenderCommand::BoxShadow {
+                rect,
+                border_radius,
+                shadows: inset_shadows,
+            });
         }
 
         // Draw border
-        if visual.border_width > 0.0 {
-            if let Some(ref border_color) = visual.border_color {
-                if border_color.a > 0 {
-                    commands.push(RenderCommand::StrokeRect {
-                        rect,
-                        color: border_color.clone(),
-                        width: visual.border_width,
-                        border_radius: visual.border_radius,
-                    });
-                }
-            }
+        let border_visible = visual
+            .border
+            .iter()
+            .any(|side| side.width > 0.0 && side.color.as_ref().is_some_and(|c| c.a > 0));
+        if border_visible {
+            commands.push(RenderCommand::Border {
+                rect,
+                sides: visual.border.clone(),
+                border_radius: visual.border_radius,
+            });
         }
 
         // Handle element-specific rendering
         match &data.element_type {
-            ElementType::Box | ElementType::Flex => {
+            ElementType::Box | ElementType::Flex | ElementType::Grid => {
                 // Container - render children
-                if let Ok(children) = layout.taffy.children(node_id) {
+                if let Ok(mut children) = layout.taffy.children(node_id) {
                     // Account for padding
                     let padding = node_layout.padding;
                     let _content_x = x + padding.left;
                     let _content_y = y + padding.top;
 
+                    // Paint siblings in stacking order: by z-index, then
+                    // positioned after in-flow elements, then tree order
+                    children.sort_by_key(|child_id| {
+                        layout
+                            .node_data
+                            .get(child_id)
+                            .map(|d| (d.visual.z_index, d.visual.positioned))
+                            .unwrap_or_default()
+                    });
+
+                    // Clip children to the padding box, following the
+                    // rounded corners inside the border
+                    let clip = visual.overflow == Overflow::Hidden;
+                    if clip {
+                        let (rect, border_radius) =
+                            padding_box(rect, node_layout.border, visual.border_radius);
+                        commands.push(RenderCommand::PushClip {
+                            rect,
+                            border_radius,
+                        });
+                    }
+
                     for child_id in children {
                         render_node(layout, child_id, x, y, commands, text_engine);
                     }
+
+                    if clip {
+                        commands.push(RenderCommand::PopClip);
+                    }
                 }
             }
 
-            ElementType::Text { content, style } => {
-                // Render text
-                let text_layout = text_engine.layout(
-                    content,
-                    &style.font_family,
-                    style.font_size,
-                    style.font_weight,
-                    style.line_height,
-                    style.text_align,
-                    width,
-                );
-
-                // Convert to render command
-                let mut lines = Vec::new();
-
-                for line in &text_layout.lines {
-                    lines.push(TextLineRender {
+            ElementType::Text {
+                content,
+                style,
+                spans,
+            } => {
+                // Wrap at the unrounded width measurement used, so text
+                // doesn't break differently because of pixel rounding
+                let wrap_width = layout.taffy.unrounded_layout(node_id).size.width;
+
+                // Reuse the text shaped during measurement when it still applies
+                let cached = match layout.taffy.get_node_context_mut(node_id) {
+                    Some(NodeContext::Text(ctx)) => ctx.cached_shape(wrap_width),
+                    _ => None,
+                };
+                let text_layout = &match cached {
+                    Some(shaped) => text_engine.layout_shaped(shaped, style, Some(wrap_width)),
+                    None => text_engine.layout(content, style, spans, Some(wrap_width)),
+                };
+
+                // `whiteSpace: nowrap` with `textOverflow: clip` cuts the
+                // line at the element's edge
+                let clip = clips_line(style);
+                if clip {
+                    commands.push(RenderCommand::PushClip {
+                        rect,
+                        border_radius: [0.0; 4],
+                    });
+                }
+
+                // Underlines and overlines paint below the text, line-throughs above
+                let (under, through) = decoration_lines(text_layout, style, x, y);
+                if !under.is_empty() {
+                    commands.push(RenderCommand::TextDecoration {
+                        lines: under,
+                        color: style.text_decoration_color.clone(),
+                        style: style.text_decoration_style,
+                    });
+                }
+
+                // Vector mode draws glyph outlines, one path per span color;
+                // falls back to <text> when no font was available to shape
+                // the content.
+                let paths = match style.text_rendering {
+                    TextRendering::Vector if spans.is_empty() => text_layout
+                        .to_path_data(x, y, None)
+                        .map(|d| vec![(d, style.color.clone())]),
+                    TextRendering::Vector => text_layout.to_path_data(x, y, None).map(|_| {
+                        spans
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(i, span)| {
+                                text_layout
+                                    .to_path_data(x, y, Some(i))
+                                    .filter(|d| !d.is_empty())
+                                    .map(|d| (d, span.color.clone()))
+                            })
+                            .collect()
+                    }),
+                    TextRendering::Text => None,
+                };
+
+                if let Some(paths) = paths {
+                    for (path_data, color) in paths {
+                        commands.push(RenderCommand::TextPath { path_data, color });
+                    }
+                } else {
+                    // Convert to render command
+                    let mut lines = Vec::new();
+
+                    for line in &text_layout.lines {
+                        lines.push(TextLineRender {
+                            x: x + line.x,
+                            y: y + line.baseline,
+                            text: line.text.clone(),
+                            char_x: line.char_x.iter().map(|cx| x + cx).collect(),
+                            spans: line
+                                .spans
+                                .iter()
+                                .map(|piece| {
+                                    let span = &spans[piece.span];
+                                    TextSpanRender {
+                                        text: piece.text.clone(),
+                                        font_family: span.font_family.clone(),
+                                        font_size: span.font_size,
+                                        font_weight: span.font_weight,
+                                        font_style: span.font_style,
+                                        font_stretch: span.font_stretch,
+                                        color: span.color.clone(),
+                                    }
+                                })
+                                .collect(),
+                        });
+                    }
+
+                    commands.push(RenderCommand::Text {
                         x,
-                        y: y + line.baseline,
-                        text: line.text.clone(),
+                        y: y + text_layout
+                            .lines
+                            .first()
+                            .map(|l| l.baseline)
+                            .unwrap_or(style.font_size),
+                        content: content.clone(),
+                        font_family: style.font_family.clone(),
+                        font_size: style.font_size,
+                        font_weight: style.font_weight,
+                        font_style: style.font_style,
+                        font_stretch: style.font_stretch,
+                        letter_spacing: style.letter_spacing,
+                        word_spacing: style.word_spacing,
+                        direction: style.direction,
+                        color: style.color.clone(),
+                        lines,
                     });
                 }
 
-                commands.push(RenderCommand::Text {
-                    x,
-                    y: y + text_layout.lines.first().map(|l| l.baseline).unwrap_or(style.font_size),
-                    content: content.clone(),
-                    font_family: style.font_family.clone(),
-                    font_size: style.font_size,
-                    font_weight: style.font_weight,
-                    color: style.color.clone(),
-                    lines,
-                });
+                if !through.is_empty() {
+                    commands.push(RenderCommand::TextDecoration {
+                        lines: through,
+                        color: style.text_decoration_color.clone(),
+                        style: style.text_decoration_style,
+                    });
+                }
+
+                if clip {
+                    commands.push(RenderCommand::PopClip);
+                }
             }
 
-            ElementType::Image { src } => {
+            ElementType::Image {
+                src,
+                width: image_width,
+                height: image_height,
+                object_fit,
+                object_position,
+            } => {
                 commands.push(RenderCommand::Image {
                     rect,
                     src: src.clone(),
                     border_radius: visual.border_radius,
+                    image_width: *image_width,
+                    image_height: *image_height,
+                    object_fit: *object_fit,
+                    object_position: *object_position,
                 });
             }
         }
@@ -253,5 +487,73 @@ fn render_node(
         if needs_opacity {
             commands.push(RenderCommand::PopOpacity);
         }
+
+        if transform.is_some() {
+            commands.push(RenderCommand::PopTransform);
+        }
     }
 }
+
+/// Place a text element's decoration lines from its font metrics. Returns the
+/// underlines and overlines, then the line-throughs.
+fn decoration_lines(
+    text_layout: &TextLayoutResult,
+    style: &TextStyleResolved,
+    x: f32,
+    y: f32,
+) -> (Vec<DecorationLine>, Vec<DecorationLine>) {
+    let decoration = style.text_decoration_line;
+    let mut under = Vec::new();
+    let mut through = Vec::new();
+
+    for line in &text_layout.lines {
+        let metrics = line.decoration;
+        if metrics.width <= 0.0 {
+            continue;
+        }
+        let baseline = y + line.baseline;
+        let thickness = style.text_decoration_thickness.unwrap_or(metrics.underline_size);
+        let segment = |top: f32, thickness: f32| DecorationLine {
+            x: x + metrics.x,
+            y: top + thickness / 2.0,
+            width: metrics.width,
+            thickness,
+        };
+
+        // Underlines and overlines grow away from the text
+        if decoration.underline {
+            under.push(segment(baseline - metrics.underline_offset, thickness));
+        }
+        if decoration.overline {
+            under.push(segment(baseline - line.ascent, thickness));
+        }
+        // Line-throughs stay centered on the font's strikeout position
+        if decoration.line_through {
+            let thickness = style.text_decoration_thickness.unwrap_or(metrics.strikethrough_size);
+            let center = baseline - metrics.strikethrough_offset + metrics.strikethrough_size / 2.0;
+            through.push(segment(center - thickness / 2.0, thickness));
+        }
+    }
+
+    (under, through)
+}
+
+/// Shrink a border box to its padding box, reducing the corner radii by the
+/// border widths so the inner edge follows the rounded corners.
+fn padding_box(rect: Rect, border: taffy::Rect<f32>, radii: [f32; 4]) -> (Rect, [f32; 4]) {
+    let [tl, tr, br, bl] = radii;
+    (
+        Rect {
+            x: rect.x + border.left,
+            y: rect.y + border.top,
+            width: (rect.width - border.left - border.right).max(0.0),
+            height: (rect.height - border.top - border.bottom).max(0.0),
+        },
+        [
+            (tl - border.left.max(border.top)).max(0.0),
+            (tr - border.right.max(border.top)).max(0.0),
+            (br - border.right.max(border.bottom)).max(0.0),
+            (bl - border.left.max(border.bottom)).max(0.0),
+        ],
+    )
+}
@@ -0,0 +1,263 @@
+//! Font subsetting for embedded `@font-face` data.
+//!
+//! Keeps glyph IDs, `cmap` and every other table intact and only empties the
+//! outlines of unused glyphs in `glyf`/`loca`. The result stays a valid font
+//! that browsers shape exactly like the original, at a fraction of the size.
+//! Glyph names in `post` are dropped as well.
+//! Fonts without TrueType outlines (CFF, WOFF/WOFF2, collections) are not
+//! subset.
+
+use std::collections::BTreeSet;
+
+/// Composite glyph component flags.
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+/// Return the MIME type of a font file based on its signature.
+pub fn font_mime_type(data: &[u8]) -> &'static str {
+    match data.get(0..4) {
+        Some(b"wOF2") => "font/woff2",
+        Some(b"wOFF") => "font/woff",
+        Some(b"OTTO") => "font/otf",
+        Some(b"ttcf") => "font/collection",
+        _ => "font/ttf",
+    }
+}
+
+/// Subset a TrueType font to the glyphs needed for `text` plus `glyphs`.
+///
+/// Returns `None` if the font isn't a single TrueType-outline font.
+pub fn subset_font(data: &[u8], text: &str, glyphs: &BTreeSet<u32>) -> Option<Vec<u8>> {
+    let face = ttf_parser::Face::parse(data, 0).ok()?;
+    let tables = read_table_directory(data)?;
+
+    let table = |tag: &[u8; 4]| tables.iter().find(|t| &t.tag == tag);
+    let head = table(b"head")?;
+    let loca = table(b"loca")?;
+    let glyf = table(b"glyf")?;
+    let glyf_data = data.get(glyf.offset..glyf.offset + glyf.length)?;
+
+    let num_glyphs = face.number_of_glyphs() as usize;
+    let long_loca = read_u16(data, head.offset + 50)? == 1;
+    let offsets = (0..=num_glyphs)
+        .map(|i| {
+            if long_loca {
+                read_u32(data, loca.offset + i * 4).map(|o| o as usize)
+            } else {
+                read_u16(data, loca.offset + i * 2).map(|o| o as usize * 2)
+            }
+        })
+        .collect::<Option<Vec<usize>>>()?;
+
+    // Glyph 0 (.notdef) is always kept
+    let mut keep: BTreeSet<u16> = BTreeSet::from([0]);
+    keep.extend(text.chars().filter_map(|c| face.glyph_index(c)).map(|g| g.0));
+    keep.extend(glyphs.iter().filter_map(|&g| u16::try_from(g).ok()));
+
+    // Composite glyphs reference other glyphs, which must be kept too
+    let mut pending: Vec<u16> = keep.iter().copied().collect();
+    while let Some(gid) = pending.pop() {
+        let Some(glyph) = glyph_bytes(glyf_data, &offsets, gid as usize) else {
+            continue;
+        };
+        for component in composite_components(glyph) {
+            if (component as usize) < num_glyphs && keep.insert(component) {
+                pending.push(component);
+            }
+        }
+    }
+
+    // Rebuild glyf and a long-format loca
+    let mut new_glyf = Vec::new();
+    let mut new_loca = Vec::with_capacity((num_glyphs + 1) * 4);
+    for gid in 0..num_glyphs {
+        new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+        if keep.contains(&(gid as u16))
+            && let Some(glyph) = glyph_bytes(glyf_data, &offsets, gid)
+        {
+            new_glyf.extend_from_slice(glyph);
+            pad4(&mut new_glyf);
+        }
+    }
+    new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+
+    let mut new_head = data.get(head.offset..head.offset + head.length)?.to_vec();
+    new_head.get_mut(8..12)?.copy_from_slice(&[0; 4]);
+    new_head.get_mut(50..52)?.copy_from_slice(&1u16.to_be_bytes());
+
+    let contents: Vec<([u8; 4], Vec<u8>)> = tables
+        .iter()
+        .map(|t| {
+            let bytes = match &t.tag {
+                b"glyf" => new_glyf.clone(),
+                b"loca" => new_loca.clone(),
+                b"head" => new_head.clone(),
+                // Glyph names aren't needed for rendering; keep a version 3 header
+                b"post" if t.length >= 32 => {
+                    let mut post = data.get(t.offset..t.offset + 32)?.to_vec();
+                    post[0..4].copy_from_slice(&0x0003_0000u32.to_be_bytes());
+                    post
+                }
+                _ => data.get(t.offset..t.offset + t.length)?.to_vec(),
+            };
+            Some((t.tag, bytes))
+        })
+        .collect::<Option<_>>()?;
+
+    Some(write_font(data.get(0..4)?, &contents))
+}
+
+struct TableRecord {
+    tag: [u8; 4],
+    offset: usize,
+    length: usize,
+}
+
+fn read_table_directory(data: &[u8]) -> Option<Vec<TableRecord>> {
+    match data.get(0..4)? {
+        [0, 1, 0, 0] | b"true" => {}
+        _ => return None,
+    }
+
+    let num_tables = read_u16(data, 4)? as usize;
+    (0..num_tables)
+        .map(|i| {
+            let record = 12 + i * 16;
+            let tag = data.get(record..record + 4)?.try_into().ok()?;
+            let offset = read_u32(data, record + 8)? as usize;
+            let length = read_u32(data, record + 12)? as usize;
+            data.get(offset..offset + length)?;
+            Some(TableRecord {
+                tag,
+                offset,
+                length,
+            })
+        })
+        .collect()
+}
+
+fn glyph_bytes<'a>(glyf: &'a [u8], offsets: &[usize], gid: usize) -> Option<&'a [u8]> {
+    let start = *offsets.get(gid)?;
+    let end = *offsets.get(gid + 1)?;
+    if end <= start {
+        return None;
+    }
+    glyf.get(start..end)
+}
+
+/// List the glyph IDs referenced by a composite glyph.
+fn composite_components(glyph: &[u8]) -> Vec<u16> {
+    let mut components = Vec::new();
+    let is_composite = read_u16(glyph, 0).is_some_and(|n| (n as i16) < 0);
+    if !is_composite {
+        return components;
+    }
+
+    let mut pos = 10;
+    while let (Some(flags), Some(gid)) = (read_u16(glyph, pos), read_u16(glyph, pos + 2)) {
+        components.push(gid);
+        pos += 4;
+        pos += if flags & ARG_1_AND_2_ARE_WORDS != 0 { 4 } else { 2 };
+        if flags & WE_HAVE_A_SCALE != 0 {
+            pos += 2;
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            pos += 4;
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            pos += 8;
+        }
+        if flags & MORE_COMPONENTS == 0 {
+            break;
+        }
+    }
+    components
+}
+
+/// Serialize an sfnt with the given tables, recomputing offsets and checksums.
+fn write_font(sfnt_version: &[u8], tables: &[([u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let num_tables = tables.len() as u16;
+    let entry_selector = (num_tables.max(1) as f32).log2().floor() as u16;
+    let search_range = (1u16 << entry_selector) * 16;
+    let range_shift = num_tables * 16 - search_range;
+
+    let mut out = Vec::new();
+    out.extend_from_slice(sfnt_version);
+    out.extend_from_slice(&num_tables.to_be_bytes());
+    out.extend_from_slice(&search_range.to_be_bytes());
+    out.extend_from_slice(&entry_selector.to_be_bytes());
+    out.extend_from_slice(&range_shift.to_be_bytes());
+
+    let mut offset = 12 + tables.len() * 16;
+    for (tag, bytes) in tables {
+        out.extend_from_slice(tag);
+        out.extend_from_slice(&checksum(bytes).to_be_bytes());
+        out.extend_from_slice(&(offset as u32).to_be_bytes());
+        out.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+        offset += bytes.len().div_ceil(4) * 4;
+    }
+
+    let mut head_offset = None;
+    for (tag, bytes) in tables {
+        if tag == b"head" {
+            head_offset = Some(out.len());
+        }
+        out.extend_from_slice(bytes);
+        pad4(&mut out);
+    }
+
+    if let Some(head_offset) = head_offset {
+        let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&out));
+        out[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+
+    out
+}
+
+fn checksum(bytes: &[u8]) -> u32 {
+    bytes.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+fn pad4(bytes: &mut Vec<u8>) {
+    while !bytes.len().is_multiple_of(4) {
+        bytes.push(0);
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_font_mime_type() {
+        assert_eq!(font_mime_type(b"wOF2...."), "font/woff2");
+        assert_eq!(font_mime_type(b"OTTO...."), "font/otf");
+        assert_eq!(font_mime_type(&[0, 1, 0, 0]), "font/ttf");
+    }
+
+    #[test]
+    fn test_checksum() {
+        assert_eq!(checksum(&[0, 0, 0, 1, 0, 0, 0, 2]), 3);
+        // Trailing bytes are zero-padded
+        assert_eq!(checksum(&[0, 0, 1]), 256);
+    }
+
+    #[test]
+    fn test_rejects_non_truetype() {
+        assert!(subset_font(b"OTTO", "a", &BTreeSet::new()).is_none());
+        assert!(subset_font(b"", "a", &BTreeSet::new()).is_none());
+    }
+}
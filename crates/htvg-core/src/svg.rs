@@ -1,6 +1,14 @@
 //! SVG generation from render commands.
 
-use crate::render::{Rect, RenderCommand, RenderTree};
+use std::collections::HashMap;
+
+use crate::element::{
+    BorderStyle, Color, ColorStop, Direction, FontStretch, FontStyle, Gradient, LinearDirection, ObjectFit, ObjectPosition,
+    PositionOffset, RadialShape, RadialSize, Shadow, TextDecorationStyle,
+};
+use crate::layout::BorderSide;
+use crate::render::{DecorationLine, Paint, Rect, RenderCommand, RenderTree, TextLineRender};
+use crate::text::css_font_family;
 use crate::FontSource;
 
 /// Options for SVG generation.
@@ -12,6 +20,9 @@ pub struct SvgOptions {
     pub pretty: bool,
     /// Decimal precision for coordinates
     pub precision: usize,
+    /// Position every character of `<text>` where shaping placed it, so
+    /// viewers don't re-shape the lines
+    pub glyph_positions: bool,
 }
 
 impl Default for SvgOptions {
@@ -20,6 +31,7 @@ impl Default for SvgOptions {
             xml_declaration: true,
             pretty: false,
             precision: 2,
+            glyph_positions: false,
         }
     }
 }
@@ -38,10 +50,57 @@ pub fn generate_svg(tree: &RenderTree, options: &SvgOptions, fonts: &[FontSource
     svg.finish()
 }
 
+/// Font and spacing attributes set on a `<text>` element, which its
+/// `<tspan>`s override.
+struct TextFont<'a> {
+    family: &'a str,
+    size: f32,
+    weight: u16,
+    style: FontStyle,
+    stretch: FontStretch,
+    letter_spacing: f32,
+    word_spacing: f32,
+    direction: Direction,
+}
+
+impl TextFont<'_> {
+    /// Text attributes, each with a leading space. Style, stretch, spacing
+    /// and direction are only set when not normal.
+    fn attrs(&self, precision: usize) -> String {
+        let mut attrs = format!(
+            " font-family=\"{}\" font-size=\"{:.p$}\" font-weight=\"{}\"",
+            escape_attr(&css_font_family(self.family)),
+            self.size,
+            self.weight,
+            p = precision
+        );
+        if self.style != FontStyle::Normal {
+            attrs.push_str(&format!(" font-style=\"{}\"", self.style.to_css()));
+        }
+        if self.stretch != FontStretch::NORMAL {
+            attrs.push_str(&format!(" font-stretch=\"{}\"", self.stretch.to_css()));
+        }
+        if self.letter_spacing != 0.0 {
+            attrs.push_str(&format!(" letter-spacing=\"{:.p$}\"", self.letter_spacing, p = precision));
+        }
+        if self.word_spacing != 0.0 {
+            attrs.push_str(&format!(" word-spacing=\"{:.p$}\"", self.word_spacing, p = precision));
+        }
+        if self.direction == Direction::Rtl {
+            attrs.push_str(" direction=\"rtl\"");
+        }
+        attrs
+    }
+}
+
 struct SvgBuilder<'a> {
     output: String,
     options: &'a SvgOptions,
     clip_id_counter: u32,
+    /// Emitted gradient definitions (markup without id -> id)
+    gradients: HashMap<String, String>,
+    /// Emitted shadow filter definitions (markup without id -> id)
+    shadow_filters: HashMap<String, String>,
 }
 
 impl<'a> SvgBuilder<'a> {
@@ -68,6 +127,8 @@ impl<'a> SvgBuilder<'a> {
             output,
             options,
             clip_id_counter: 0,
+            gradients: HashMap::new(),
+            shadow_filters: HashMap::new(),
         }
     }
 
@@ -80,11 +141,18 @@ impl<'a> SvgBuilder<'a> {
         self.output.push_str("<defs><style>");
         for font in url_fonts {
             let url = font.url.as_ref().unwrap();
+            let mut descriptors = format!("font-weight: {};", font.weight);
+            if font.style != FontStyle::Normal {
+                descriptors.push_str(&format!(" font-style: {};", font.style.to_css()));
+            }
+            if font.stretch != FontStretch::NORMAL {
+                descriptors.push_str(&format!(" font-stretch: {};", font.stretch.to_css()));
+            }
             self.output.push_str(&format!(
-                "@font-face {{ font-family: '{}'; src: url('{}'); font-weight: {}; font-display: swap; }}",
+                "@font-face {{ font-family: '{}'; src: url('{}'); {} font-display: swap; }}",
                 escape_xml(&font.family),
                 escape_xml(url),
-                font.weight
+                descriptors
             ));
         }
         self.output.push_str("</style></defs>");
@@ -92,44 +160,81 @@ impl<'a> SvgBuilder<'a> {
 
     fn render_command(&mut self, cmd: &RenderCommand) {
         match cmd {
+            RenderCommand::BoxShadow {
+                rect,
+                border_radius,
+                shadows,
+            } => {
+                self.render_box_shadow(rect, border_radius, shadows);
+            }
+
             RenderCommand::FillRect {
                 rect,
-                color,
+                paint,
                 border_radius,
             } => {
-                self.render_fill_rect(rect, color, border_radius);
+                self.render_fill_rect(rect, paint, border_radius);
             }
 
-            RenderCommand::StrokeRect {
+            RenderCommand::Border {
                 rect,
-                color,
-                width,
+                sides,
                 border_radius,
             } => {
-                self.render_stroke_rect(rect, color, *width, border_radius);
+                self.render_border(rect, sides, border_radius);
             }
 
             RenderCommand::Text {
                 font_family,
                 font_size,
                 font_weight,
+                font_style,
+                font_stretch,
+                letter_spacing,
+                word_spacing,
+                direction,
                 color,
                 lines,
                 ..
             } => {
-                self.render_text(font_family, *font_size, *font_weight, color, lines);
+                let font = TextFont {
+                    family: font_family,
+                    size: *font_size,
+                    weight: *font_weight,
+                    style: *font_style,
+                    stretch: *font_stretch,
+                    letter_spacing: *letter_spacing,
+                    word_spacing: *word_spacing,
+                    direction: *direction,
+                };
+                self.render_text(&font, color, lines);
             }
 
             RenderCommand::TextPath { path_data, color } => {
                 self.render_text_path(path_data, color);
             }
 
+            RenderCommand::TextDecoration { lines, color, style } => {
+                self.render_text_decoration(lines, color, *style);
+            }
+
             RenderCommand::Image {
                 rect,
                 src,
                 border_radius,
+                image_width,
+                image_height,
+                object_fit,
+                object_position,
             } => {
-                self.render_image(rect, src, border_radius);
+                self.render_image(
+                    rect,
+                    src,
+                    border_radius,
+                    (*image_width, *image_height),
+                    *object_fit,
+                    *object_position,
+                );
             }
 
             RenderCommand::PushClip { rect, border_radius } => {
@@ -140,6 +245,14 @@ impl<'a> SvgBuilder<'a> {
                 self.pop_clip();
             }
 
+            RenderCommand::PushTransform { matrix } => {
+                self.push_transform(matrix);
+            }
+
+            RenderCommand::PopTransform => {
+                self.pop_transform();
+            }
+
             RenderCommand::PushOpacity { opacity } => {
                 self.push_opacity(*opacity);
             }
@@ -153,14 +266,30 @@ impl<'a> SvgBuilder<'a> {
     fn render_fill_rect(
         &mut self,
         rect: &Rect,
-        color: &crate::element::Color,
+        paint: &Paint,
         border_radius: &[f32; 4],
     ) {
-        if color.a == 0 {
-            return;
-        }
-
         let p = self.options.precision;
+        let fill = match paint {
+            Paint::Color(color) if color.a == 0 => return,
+            Paint::Color(color) => color.to_css(),
+            Paint::Gradient(gradient) => {
+                // Drawn at the origin and moved into place, so the gradient
+                // only depends on the box size
+                let id = self.gradient_id(gradient, rect.width, rect.height);
+                let local = Rect { x: 0.0, y: 0.0, ..*rect };
+                let attrs = format!(
+                    " transform=\"translate({:.p$} {:.p$})\" fill=\"url(#{})\"",
+                    rect.x,
+                    rect.y,
+                    id,
+                    p = p
+                );
+                self.output.push_str(&shape_markup(&local, border_radius, p, &attrs));
+                return;
+            }
+        };
+
         let has_radius = border_radius.iter().any(|&r| r > 0.0);
 
         if has_radius && !all_same(border_radius) {
@@ -168,7 +297,7 @@ impl<'a> SvgBuilder<'a> {
             self.output.push_str(&format!(
                 "<path d=\"{}\" fill=\"{}\"/>",
                 rounded_rect_path(rect, border_radius, p),
-                color.to_css()
+                fill
             ));
         } else if has_radius {
             // Same radius on all corners
@@ -180,7 +309,7 @@ impl<'a> SvgBuilder<'a> {
                 rect.width,
                 rect.height,
                 border_radius[0],
-                color.to_css(),
+                fill,
                 p = p
             ));
         } else {
@@ -192,17 +321,245 @@ impl<'a> SvgBuilder<'a> {
                 rect.y,
                 rect.width,
                 rect.height,
-                color.to_css(),
+                fill,
                 p = p
             ));
         }
     }
 
+    /// Return the id of a gradient definition for a box of the given size at
+    /// the origin, emitting it on first use.
+    ///
+    /// Gradients are laid out in user space so angles and radii follow CSS
+    /// for non-square boxes; identical gradients over boxes of the same size
+    /// share one definition.
+    fn gradient_id(&mut self, gradient: &Gradient, width: f32, height: f32) -> String {
+        let p = self.options.precision;
+        let rect = &Rect { x: 0.0, y: 0.0, width, height };
+
+        let (element, attrs, length, stops) = match gradient {
+            Gradient::Linear { direction, stops } => {
+                let angle = match *direction {
+                    LinearDirection::Angle(deg) => deg.to_radians(),
+                    // The gradient line is perpendicular to the diagonal
+                    // through the two neighbouring corners
+                    LinearDirection::Corner { x, y } => (x * rect.height).atan2(-y * rect.width),
+                };
+                let (sin, cos) = angle.sin_cos();
+                let length = (rect.width * sin).abs() + (rect.height * cos).abs();
+                let (cx, cy) = (rect.x + rect.width / 2.0, rect.y + rect.height / 2.0);
+                let (dx, dy) = (sin * length / 2.0, -cos * length / 2.0);
+                let attrs = format!(
+                    "x1=\"{:.p$}\" y1=\"{:.p$}\" x2=\"{:.p$}\" y2=\"{:.p$}\"",
+                    snap_zero(cx - dx),
+                    snap_zero(cy - dy),
+                    snap_zero(cx + dx),
+                    snap_zero(cy + dy),
+                    p = p
+                );
+                ("linearGradient", attrs, length, stops)
+            }
+            Gradient::Radial {
+                shape,
+                size,
+                center,
+                stops,
+            } => {
+                let cx = rect.x + center.x.resolve(rect.width);
+                let cy = rect.y + center.y.resolve(rect.height);
+                let (rx, ry) = radial_radii(*shape, *size, rect, cx, cy);
+                let mut attrs = format!(
+                    "cx=\"{:.p$}\" cy=\"{:.p$}\" r=\"{:.p$}\"",
+                    cx,
+                    cy,
+                    rx,
+                    p = p
+                );
+                // Ellipses are circles scaled vertically around the center
+                if rx > 0.0 && (rx - ry).abs() > f32::EPSILON {
+                    let scale = ry / rx;
+                    attrs.push_str(&format!(
+                        " gradientTransform=\"matrix(1 0 0 {:.4} 0 {:.p$})\"",
+                        scale,
+                        cy * (1.0 - scale),
+                        p = p
+                    ));
+                }
+                ("radialGradient", attrs, rx, stops)
+            }
+        };
+
+        let mut markup = format!("gradientUnits=\"userSpaceOnUse\" {}>", attrs);
+        for (offset, color) in resolve_stops(stops, length) {
+            markup.push_str(&format!(
+                "<stop offset=\"{:.4}\" stop-color=\"{}\"",
+                offset,
+                rgb_hex(color)
+            ));
+            if color.a < 255 {
+                markup.push_str(&format!(" stop-opacity=\"{:.3}\"", color.a as f32 / 255.0));
+            }
+            markup.push_str("/>");
+        }
+        markup.push_str(&format!("</{}>", element));
+
+        if let Some(id) = self.gradients.get(&markup) {
+            return id.clone();
+        }
+
+        let id = format!("grad-{}", self.gradients.len());
+        self.output.push_str(&format!(
+            "<defs><{} id=\"{}\" {}</defs>",
+            element, id, markup
+        ));
+        self.gradients.insert(markup, id.clone());
+        id
+    }
+
+    fn render_box_shadow(&mut self, rect: &Rect, border_radius: &[f32; 4], shadows: &[Shadow]) {
+        let p = self.options.precision;
+
+        // The first shadow is painted on top
+        for shadow in shadows.iter().rev() {
+            if shadow.color.a == 0 {
+                continue;
+            }
+
+            if shadow.inset {
+                // Blur a frame whose hole is the box shrunk by the spread,
+                // then clip it to the box
+                let margin = shadow.blur * 1.5 + shadow.offset_x.abs() + shadow.offset_y.abs() + 1.0;
+                let frame = outset_rect(rect, margin);
+                let hole = outset_rect(rect, -shadow.spread);
+                let mut d = rounded_rect_path(&frame, &[0.0; 4], p);
+                if hole.width > 0.0 && hole.height > 0.0 {
+                    d.push(' ');
+                    d.push_str(&rounded_rect_path(&hole, &spread_radii(border_radius, -shadow.spread), p));
+                }
+
+                let filter = self.shadow_filter_id(shadow, &frame);
+                let clip_id = self.clip_id_counter;
+                self.clip_id_counter += 1;
+                self.output.push_str(&format!(
+                    "<defs><clipPath id=\"clip-{}\">{}</clipPath></defs>\
+                     <g clip-path=\"url(#clip-{})\"><path d=\"{}\" fill-rule=\"evenodd\" filter=\"url(#{})\"/></g>",
+                    clip_id,
+                    shape_markup(rect, border_radius, p, ""),
+                    clip_id,
+                    d,
+                    filter
+                ));
+            } else {
+                let shape = outset_rect(rect, shadow.spread);
+                if shape.width <= 0.0 || shape.height <= 0.0 {
+                    continue;
+                }
+                let filter = self.shadow_filter_id(shadow, &shape);
+                self.output.push_str(&shape_markup(
+                    &shape,
+                    &spread_radii(border_radius, shadow.spread),
+                    p,
+                    &format!(" filter=\"url(#{})\"", filter),
+                ));
+            }
+        }
+    }
+
+    /// Return the id of a drop shadow filter for a shape with the given
+    /// bounds, emitting it on first use.
+    ///
+    /// The filter region is relative to the shape's bounding box, so shapes
+    /// of the same size with identical shadows share one filter.
+    fn shadow_filter_id(&mut self, shadow: &Shadow, bounds: &Rect) -> String {
+        // A Gaussian blur fades out within three standard deviations
+        let std_dev = shadow.blur / 2.0;
+        let margin = std_dev * 3.0;
+        let (w, h) = (bounds.width.max(1.0), bounds.height.max(1.0));
+
+        let mut markup = format!(
+            "x=\"{:.4}\" y=\"{:.4}\" width=\"{:.4}\" height=\"{:.4}\" color-interpolation-filters=\"sRGB\">",
+            (shadow.offset_x.min(0.0) - margin) / w,
+            (shadow.offset_y.min(0.0) - margin) / h,
+            (w + shadow.offset_x.abs() + margin * 2.0) / w,
+            (h + shadow.offset_y.abs() + margin * 2.0) / h,
+        );
+        if std_dev > 0.0 {
+            markup.push_str(&format!(
+                "<feGaussianBlur in=\"SourceAlpha\" stdDeviation=\"{:.2}\"/><feOffset",
+                std_dev
+            ));
+        } else {
+            markup.push_str("<feOffset in=\"SourceAlpha\"");
+        }
+        markup.push_str(&format!(
+            " dx=\"{:.2}\" dy=\"{:.2}\" result=\"offset\"/><feFlood flood-color=\"{}\"",
+            shadow.offset_x,
+            shadow.offset_y,
+            rgb_hex(&shadow.color)
+        ));
+        if shadow.color.a < 255 {
+            markup.push_str(&format!(" flood-opacity=\"{:.3}\"", shadow.color.a as f32 / 255.0));
+        }
+        markup.push_str("/><feComposite in2=\"offset\" operator=\"in\"/></filter>");
+
+        if let Some(id) = self.shadow_filters.get(&markup) {
+            return id.clone();
+        }
+
+        let id = format!("shadow-{}", self.shadow_filters.len());
+        self.output
+            .push_str(&format!("<defs><filter id=\"{}\" {}</defs>", id, markup));
+        self.shadow_filters.insert(markup, id.clone());
+        id
+    }
+
+    fn render_border(&mut self, rect: &Rect, sides: &[BorderSide; 4], border_radius: &[f32; 4]) {
+        let p = self.options.precision;
+        let widths = sides.each_ref().map(|side| side.width);
+
+        // A uniform border is drawn as one shape
+        if sides.iter().all(|side| *side == sides[0]) {
+            let side = &sides[0];
+            if let Some(color) = &side.color {
+                if side.style == BorderStyle::Double {
+                    let markup = border_markup(rect, border_radius, widths, side.style, color, side.width, p);
+                    self.output.push_str(&markup);
+                } else {
+                    self.render_stroke_rect(rect, color, side.width, side.style, border_radius);
+                }
+            }
+            return;
+        }
+
+        // Otherwise each side is drawn over the whole ring and clipped to
+        // its wedge, so adjacent sides meet diagonally at the corners
+        for (index, side) in sides.iter().enumerate() {
+            let Some(color) = &side.color else {
+                continue;
+            };
+            if color.a == 0 || side.width <= 0.0 {
+                continue;
+            }
+
+            let clip_id = self.clip_id_counter;
+            self.clip_id_counter += 1;
+            self.output.push_str(&format!(
+                "<defs><clipPath id=\"clip-{}\"><polygon points=\"{}\"/></clipPath></defs>\
+                 <g clip-path=\"url(#clip-{})\">{}</g>",
+                clip_id,
+                border_wedge(rect, widths, index, p),
+                clip_id,
+                border_markup(rect, border_radius, widths, side.style, color, side.width, p)
+            ));
+        }
+    }
+
     fn render_stroke_rect(
         &mut self,
         rect: &Rect,
-        color: &crate::element::Color,
+        color: &Color,
         stroke_width: f32,
+        style: BorderStyle,
         border_radius: &[f32; 4],
     ) {
         if color.a == 0 || stroke_width <= 0.0 {
@@ -211,6 +568,7 @@ impl<'a> SvgBuilder<'a> {
 
         let p = self.options.precision;
         let has_radius = border_radius.iter().any(|&r| r > 0.0);
+        let dash = dash_attrs(style, stroke_width, p);
 
         // Inset the rect by half stroke width for proper border rendering
         let inset = stroke_width / 2.0;
@@ -223,16 +581,17 @@ impl<'a> SvgBuilder<'a> {
 
         if has_radius && !all_same(border_radius) {
             self.output.push_str(&format!(
-                "<path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{:.p$}\"/>",
+                "<path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{:.p$}\"{}/>",
                 rounded_rect_path(&inner_rect, border_radius, p),
                 color.to_css(),
                 stroke_width,
+                dash,
                 p = p
             ));
         } else if has_radius {
             self.output.push_str(&format!(
                 "<rect x=\"{:.p$}\" y=\"{:.p$}\" width=\"{:.p$}\" height=\"{:.p$}\" \
-                 rx=\"{:.p$}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{:.p$}\"/>",
+                 rx=\"{:.p$}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{:.p$}\"{}/>",
                 inner_rect.x,
                 inner_rect.y,
                 inner_rect.width,
@@ -240,31 +599,26 @@ impl<'a> SvgBuilder<'a> {
                 border_radius[0],
                 color.to_css(),
                 stroke_width,
+                dash,
                 p = p
             ));
         } else {
             self.output.push_str(&format!(
                 "<rect x=\"{:.p$}\" y=\"{:.p$}\" width=\"{:.p$}\" height=\"{:.p$}\" \
-                 fill=\"none\" stroke=\"{}\" stroke-width=\"{:.p$}\"/>",
+                 fill=\"none\" stroke=\"{}\" stroke-width=\"{:.p$}\"{}/>",
                 inner_rect.x,
                 inner_rect.y,
                 inner_rect.width,
                 inner_rect.height,
                 color.to_css(),
                 stroke_width,
+                dash,
                 p = p
             ));
         }
     }
 
-    fn render_text(
-        &mut self,
-        font_family: &str,
-        font_size: f32,
-        font_weight: u16,
-        color: &crate::element::Color,
-        lines: &[crate::render::TextLineRender],
-    ) {
+    fn render_text(&mut self, font: &TextFont, color: &Color, lines: &[TextLineRender]) {
         let p = self.options.precision;
 
         if lines.is_empty() {
@@ -274,38 +628,27 @@ impl<'a> SvgBuilder<'a> {
         if lines.len() == 1 {
             let line = &lines[0];
             self.output.push_str(&format!(
-                "<text x=\"{:.p$}\" y=\"{:.p$}\" \
-                 fill=\"{}\" \
-                 font-family=\"'{}', sans-serif\" \
-                 font-size=\"{:.p$}\" \
-                 font-weight=\"{}\">{}</text>",
-                line.x,
+                "<text x=\"{}\" y=\"{:.p$}\" fill=\"{}\"{}>{}</text>",
+                self.line_x(line),
                 line.y,
                 color.to_css(),
-                escape_xml(font_family),
-                font_size,
-                font_weight,
-                escape_xml(&line.text),
+                font.attrs(p),
+                self.line_content(line, font, color),
                 p = p
             ));
         } else {
             self.output.push_str(&format!(
-                "<text fill=\"{}\" \
-                 font-family=\"'{}', sans-serif\" \
-                 font-size=\"{:.p$}\" \
-                 font-weight=\"{}\">",
+                "<text fill=\"{}\"{}>",
                 color.to_css(),
-                escape_xml(font_family),
-                font_size,
-                font_weight,
-                p = p
+                font.attrs(p)
             ));
             for line in lines {
+                let content = self.line_content(line, font, color);
                 self.output.push_str(&format!(
-                    "<tspan x=\"{:.p$}\" y=\"{:.p$}\">{}</tspan>",
-                    line.x,
+                    "<tspan x=\"{}\" y=\"{:.p$}\">{}</tspan>",
+                    self.line_x(line),
                     line.y,
-                    escape_xml(&line.text),
+                    content,
                     p = p
                 ));
             }
@@ -313,7 +656,67 @@ impl<'a> SvgBuilder<'a> {
         }
     }
 
-    fn render_text_path(&mut self, path_data: &str, color: &crate::element::Color) {
+    /// The `x` attribute of a line: where it starts, or with
+    /// `glyph_positions` the position of each character.
+    fn line_x(&self, line: &TextLineRender) -> String {
+        let p = self.options.precision;
+        if !self.options.glyph_positions || line.char_x.is_empty() {
+            return format!("{:.p$}", line.x, p = p);
+        }
+
+        // Viewers address characters in UTF-16 code units
+        line.text
+            .chars()
+            .zip(&line.char_x)
+            .flat_map(|(c, &x)| std::iter::repeat_n(x, c.len_utf16()))
+            .map(|x| format!("{:.p$}", x, p = p))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Escaped line content, with a `<tspan>` per styled span that only sets
+    /// the attributes differing from the enclosing `<text>`.
+    fn line_content(&self, line: &TextLineRender, font: &TextFont, color: &Color) -> String {
+        if line.spans.is_empty() {
+            return escape_xml(&line.text);
+        }
+
+        let p = self.options.precision;
+        let mut content = String::new();
+        for span in &line.spans {
+            let mut attrs = String::new();
+            if span.font_family != font.family {
+                attrs.push_str(&format!(
+                    " font-family=\"{}\"",
+                    escape_attr(&css_font_family(&span.font_family))
+                ));
+            }
+            if (span.font_size - font.size).abs() > f32::EPSILON {
+                attrs.push_str(&format!(" font-size=\"{:.p$}\"", span.font_size, p = p));
+            }
+            if span.font_weight != font.weight {
+                attrs.push_str(&format!(" font-weight=\"{}\"", span.font_weight));
+            }
+            if span.font_style != font.style {
+                attrs.push_str(&format!(" font-style=\"{}\"", span.font_style.to_css()));
+            }
+            if span.font_stretch != font.stretch {
+                attrs.push_str(&format!(" font-stretch=\"{}\"", span.font_stretch.to_css()));
+            }
+            if span.color.to_css() != color.to_css() {
+                attrs.push_str(&format!(" fill=\"{}\"", span.color.to_css()));
+            }
+
+            if attrs.is_empty() {
+                content.push_str(&escape_xml(&span.text));
+            } else {
+                content.push_str(&format!("<tspan{}>{}</tspan>", attrs, escape_xml(&span.text)));
+            }
+        }
+        content
+    }
+
+    fn render_text_path(&mut self, path_data: &str, color: &Color) {
         self.output.push_str(&format!(
             "<path d=\"{}\" fill=\"{}\"/>",
             path_data,
@@ -321,11 +724,92 @@ impl<'a> SvgBuilder<'a> {
         ));
     }
 
-    fn render_image(&mut self, rect: &Rect, src: &str, border_radius: &[f32; 4]) {
+    fn render_text_decoration(
+        &mut self,
+        lines: &[DecorationLine],
+        color: &Color,
+        style: TextDecorationStyle,
+    ) {
+        let p = self.options.precision;
+
+        for line in lines {
+            let stroke = format!(
+                "fill=\"none\" stroke=\"{}\" stroke-width=\"{:.p$}\"",
+                color.to_css(),
+                line.thickness,
+                p = p
+            );
+            let x2 = line.x + line.width;
+            let t = line.thickness;
+            match style {
+                TextDecorationStyle::Solid | TextDecorationStyle::Dashed | TextDecorationStyle::Dotted => {
+                    let dash = match style {
+                        TextDecorationStyle::Dashed => dash_attrs(BorderStyle::Dashed, t, p),
+                        TextDecorationStyle::Dotted => dash_attrs(BorderStyle::Dotted, t, p),
+                        _ => String::new(),
+                    };
+                    self.output.push_str(&format!(
+                        "<line x1=\"{:.p$}\" y1=\"{:.p$}\" x2=\"{:.p$}\" y2=\"{:.p$}\" {}{}/>",
+                        line.x, line.y, x2, line.y, stroke, dash,
+                        p = p
+                    ));
+                }
+                // Two lines of the full thickness, one thickness apart
+                TextDecorationStyle::Double => {
+                    self.output.push_str(&format!(
+                        "<path d=\"M{:.p$} {:.p$}H{:.p$}M{:.p$} {:.p$}H{:.p$}\" {}/>",
+                        line.x, line.y - t, x2, line.x, line.y + t, x2, stroke,
+                        p = p
+                    ));
+                }
+                TextDecorationStyle::Wavy => {
+                    self.output.push_str(&format!(
+                        "<path d=\"{}\" {}/>",
+                        wavy_path(line, p),
+                        stroke
+                    ));
+                }
+            }
+        }
+    }
+
+    fn render_image(
+        &mut self,
+        rect: &Rect,
+        src: &str,
+        border_radius: &[f32; 4],
+        image_size: (f32, f32),
+        object_fit: ObjectFit,
+        object_position: ObjectPosition,
+    ) {
         let p = self.options.precision;
         let has_radius = border_radius.iter().any(|&r| r > 0.0);
 
-        if has_radius {
+        // Keyword-aligned positions map directly onto preserveAspectRatio;
+        // anything else is placed explicitly and clipped to the box.
+        let (image_rect, aspect, overflows) = match object_fit {
+            ObjectFit::Fill => (*rect, "none".to_string(), false),
+            ObjectFit::Contain | ObjectFit::Cover => {
+                let cover = object_fit == ObjectFit::Cover;
+                match (
+                    align_keyword(object_position.x),
+                    align_keyword(object_position.y),
+                ) {
+                    (Some(x), Some(y)) => (
+                        *rect,
+                        format!("x{}Y{} {}", x, y, if cover { "slice" } else { "meet" }),
+                        cover,
+                    ),
+                    _ => (
+                        place_image(rect, image_size, cover, object_position),
+                        "none".to_string(),
+                        true,
+                    ),
+                }
+            }
+        };
+
+        let clip_id = if has_radius || overflows {
             // Need to clip the image
             let clip_id = self.clip_id_counter;
             self.clip_id_counter += 1;
@@ -348,30 +832,27 @@ impl<'a> SvgBuilder<'a> {
             }
 
             self.output.push_str("</clipPath></defs>");
-
-            self.output.push_str(&format!(
-                "<image x=\"{:.p$}\" y=\"{:.p$}\" width=\"{:.p$}\" height=\"{:.p$}\" \
-                 xlink:href=\"{}\" clip-path=\"url(#img-clip-{})\"/>",
-                rect.x,
-                rect.y,
-                rect.width,
-                rect.height,
-                escape_xml(src),
-                clip_id,
-                p = p
-            ));
+            Some(clip_id)
         } else {
-            self.output.push_str(&format!(
-                "<image x=\"{:.p$}\" y=\"{:.p$}\" width=\"{:.p$}\" height=\"{:.p$}\" \
-                 xlink:href=\"{}\"/>",
-                rect.x,
-                rect.y,
-                rect.width,
-                rect.height,
-                escape_xml(src),
-                p = p
-            ));
+            None
+        };
+
+        self.output.push_str(&format!(
+            "<image x=\"{:.p$}\" y=\"{:.p$}\" width=\"{:.p$}\" height=\"{:.p$}\" \
+             preserveAspectRatio=\"{}\" xlink:href=\"{}\"",
+            image_rect.x,
+            image_rect.y,
+            image_rect.width,
+            image_rect.height,
+            aspect,
+            escape_xml(src),
+            p = p
+        ));
+        if let Some(clip_id) = clip_id {
+            self.output
+                .push_str(&format!(" clip-path=\"url(#img-clip-{})\"", clip_id));
         }
+        self.output.push_str("/>");
     }
 
     fn push_clip(&mut self, rect: &Rect, border_radius: &[f32; 4]) {
@@ -413,6 +894,24 @@ impl<'a> SvgBuilder<'a> {
         self.output.push_str("</g>");
     }
 
+    fn push_transform(&mut self, matrix: &[f32; 6]) {
+        let [a, b, c, d, e, f] = matrix.map(snap_zero);
+        self.output.push_str(&format!(
+            "<g transform=\"matrix({:.4} {:.4} {:.4} {:.4} {:.p$} {:.p$})\">",
+            a,
+            b,
+            c,
+            d,
+            e,
+            f,
+            p = self.options.precision
+        ));
+    }
+
+    fn pop_transform(&mut self) {
+        self.output.push_str("</g>");
+    }
+
     fn push_opacity(&mut self, opacity: f32) {
         self.output
             .push_str(&format!("<g opacity=\"{:.2}\">", opacity));
@@ -490,6 +989,300 @@ fn rounded_rect_path(rect: &Rect, radii: &[f32; 4], precision: usize) -> String
     )
 }
 
+/// preserveAspectRatio alignment keyword for a start/center/end position.
+fn align_keyword(offset: PositionOffset) -> Option<&'static str> {
+    match offset {
+        PositionOffset::Percent(0.0) => Some("Min"),
+        PositionOffset::Percent(50.0) => Some("Mid"),
+        PositionOffset::Percent(100.0) => Some("Max"),
+        _ => None,
+    }
+}
+
+/// Scale an image of the given intrinsic size to contain or cover `rect`,
+/// positioned by `position` like CSS `object-position`.
+fn place_image(rect: &Rect, (width, height): (f32, f32), cover: bool, position: ObjectPosition) -> Rect {
+    if width <= 0.0 || height <= 0.0 {
+        return *rect;
+    }
+
+    let sx = rect.width / width;
+    let sy = rect.height / height;
+    let scale = if cover { sx.max(sy) } else { sx.min(sy) };
+    let (w, h) = (width * scale, height * scale);
+
+    Rect {
+        x: rect.x + position.x.resolve(rect.width - w),
+        y: rect.y + position.y.resolve(rect.height - h),
+        width: w,
+        height: h,
+    }
+}
+
+/// Stroke dash attributes for a border style.
+fn dash_attrs(style: BorderStyle, width: f32, precision: usize) -> String {
+    let p = precision;
+    match style {
+        BorderStyle::Dashed => format!(" stroke-dasharray=\"{:.p$} {:.p$}\"", width * 3.0, width * 3.0, p = p),
+        BorderStyle::Dotted => format!(
+            " stroke-dasharray=\"0 {:.p$}\" stroke-linecap=\"round\"",
+            width * 2.0,
+            p = p
+        ),
+        BorderStyle::Solid | BorderStyle::Double => String::new(),
+    }
+}
+
+/// Path data for a wavy decoration: quadratic half-waves alternating above
+/// and below the line, stretched to end exactly at its width.
+fn wavy_path(line: &DecorationLine, precision: usize) -> String {
+    let p = precision;
+    let amplitude = (line.thickness * 1.5).max(1.0);
+    let count = (line.width / (amplitude * 3.0)).round().max(1.0);
+    let half = line.width / count;
+
+    let mut d = format!(
+        "M{:.p$} {:.p$}q{:.p$} {:.p$} {:.p$} 0",
+        line.x,
+        line.y,
+        half / 2.0,
+        -amplitude * 2.0,
+        half,
+        p = p
+    );
+    for _ in 1..count as usize {
+        d.push_str(&format!("t{:.p$} 0", half, p = p));
+    }
+    d
+}
+
+/// Shrink a rounded rectangle by a fraction of each side's border width.
+fn inset_box(rect: &Rect, radii: &[f32; 4], widths: [f32; 4], fraction: f32) -> (Rect, [f32; 4]) {
+    let [top, right, bottom, left] = widths.map(|w| w * fraction);
+    let [tl, tr, br, bl] = *radii;
+    (
+        Rect {
+            x: rect.x + left,
+            y: rect.y + top,
+            width: (rect.width - left - right).max(0.0),
+            height: (rect.height - top - bottom).max(0.0),
+        },
+        [
+            if tl > 0.0 { (tl - left.max(top)).max(0.0) } else { 0.0 },
+            if tr > 0.0 { (tr - right.max(top)).max(0.0) } else { 0.0 },
+            if br > 0.0 { (br - right.max(bottom)).max(0.0) } else { 0.0 },
+            if bl > 0.0 { (bl - left.max(bottom)).max(0.0) } else { 0.0 },
+        ],
+    )
+}
+
+/// Path data for the band between two fractions of the border widths.
+fn border_band(rect: &Rect, radii: &[f32; 4], widths: [f32; 4], from: f32, to: f32, precision: usize) -> String {
+    let (outer, outer_radii) = inset_box(rect, radii, widths, from);
+    let (inner, inner_radii) = inset_box(rect, radii, widths, to);
+    format!(
+        "{} {}",
+        rounded_rect_path(&outer, &outer_radii, precision),
+        rounded_rect_path(&inner, &inner_radii, precision)
+    )
+}
+
+/// Markup for a border drawn with one side's style and color over the whole ring.
+fn border_markup(
+    rect: &Rect,
+    radii: &[f32; 4],
+    widths: [f32; 4],
+    style: BorderStyle,
+    color: &Color,
+    width: f32,
+    precision: usize,
+) -> String {
+    let p = precision;
+    match style {
+        BorderStyle::Solid => format!(
+            "<path d=\"{}\" fill=\"{}\" fill-rule=\"evenodd\"/>",
+            border_band(rect, radii, widths, 0.0, 1.0, p),
+            color.to_css()
+        ),
+        // Two lines of a third of the width each, with a gap between
+        BorderStyle::Double => format!(
+            "<path d=\"{} {}\" fill=\"{}\" fill-rule=\"evenodd\"/>",
+            border_band(rect, radii, widths, 0.0, 1.0 / 3.0, p),
+            border_band(rect, radii, widths, 2.0 / 3.0, 1.0, p),
+            color.to_css()
+        ),
+        // Dashes follow the middle of the border
+        BorderStyle::Dashed | BorderStyle::Dotted => {
+            let (center, center_radii) = inset_box(rect, radii, widths, 0.5);
+            format!(
+                "<path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{:.p$}\"{}/>",
+                rounded_rect_path(&center, &center_radii, p),
+                color.to_css(),
+                width,
+                dash_attrs(style, width, p),
+                p = p
+            )
+        }
+    }
+}
+
+/// Polygon points of the region owned by one border side (0 = top, then
+/// clockwise). Sides meet along lines from each outer corner through the
+/// inner corner, extended far enough to cover rounded corners.
+fn border_wedge(rect: &Rect, widths: [f32; 4], side: usize, precision: usize) -> String {
+    let p = precision;
+    let [top, right, bottom, left] = widths;
+    let (x0, y0, x1, y1) = (rect.x, rect.y, rect.x + rect.width, rect.y + rect.height);
+    let depth = rect.width.min(rect.height) / 2.0;
+
+    // Point along the corner diagonal, reaching `depth` into the box
+    let diagonal = |(cx, cy): (f32, f32), (dx, dy): (f32, f32), (sx, sy): (f32, f32)| {
+        let (dx, dy) = if dx <= 0.0 && dy <= 0.0 { (1.0, 1.0) } else { (dx, dy) };
+        let scale = depth / dx.max(dy);
+        (cx + sx * dx * scale, cy + sy * dy * scale)
+    };
+    let tl = diagonal((x0, y0), (left, top), (1.0, 1.0));
+    let tr = diagonal((x1, y0), (right, top), (-1.0, 1.0));
+    let br = diagonal((x1, y1), (right, bottom), (-1.0, -1.0));
+    let bl = diagonal((x0, y1), (left, bottom), (1.0, -1.0));
+
+    let points = match side {
+        0 => [(x0, y0), (x1, y0), tr, tl],
+        1 => [(x1, y0), (x1, y1), br, tr],
+        2 => [(x1, y1), (x0, y1), bl, br],
+        _ => [(x0, y1), (x0, y0), tl, bl],
+    };
+    points
+        .iter()
+        .map(|(x, y)| format!("{:.p$},{:.p$}", x, y, p = p))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Emit a `<rect>` or `<path>` for a rectangle with the given corner radii.
+fn shape_markup(rect: &Rect, radii: &[f32; 4], precision: usize, attrs: &str) -> String {
+    let p = precision;
+    if radii.iter().any(|&r| r > 0.0) && !all_same(radii) {
+        format!("<path d=\"{}\"{}/>", rounded_rect_path(rect, radii, p), attrs)
+    } else if radii[0] > 0.0 {
+        format!(
+            "<rect x=\"{:.p$}\" y=\"{:.p$}\" width=\"{:.p$}\" height=\"{:.p$}\" rx=\"{:.p$}\"{}/>",
+            rect.x, rect.y, rect.width, rect.height, radii[0], attrs,
+            p = p
+        )
+    } else {
+        format!(
+            "<rect x=\"{:.p$}\" y=\"{:.p$}\" width=\"{:.p$}\" height=\"{:.p$}\"{}/>",
+            rect.x, rect.y, rect.width, rect.height, attrs,
+            p = p
+        )
+    }
+}
+
+/// Grow (or shrink, for negative amounts) a rectangle on every side.
+fn outset_rect(rect: &Rect, amount: f32) -> Rect {
+    Rect {
+        x: rect.x - amount,
+        y: rect.y - amount,
+        width: rect.width + amount * 2.0,
+        height: rect.height + amount * 2.0,
+    }
+}
+
+/// Adjust corner radii for a shadow spread; square corners stay square.
+fn spread_radii(radii: &[f32; 4], spread: f32) -> [f32; 4] {
+    radii.map(|r| if r > 0.0 { (r + spread).max(0.0) } else { 0.0 })
+}
+
+/// Flush float noise around zero so it isn't printed as "-0.00".
+fn snap_zero(v: f32) -> f32 {
+    if v.abs() < 1e-4 { 0.0 } else { v }
+}
+
+/// Format the RGB part of a color as `#rrggbb`.
+fn rgb_hex(color: &Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+/// Compute the horizontal and vertical radii of a radial gradient's ending shape.
+fn radial_radii(shape: RadialShape, size: RadialSize, rect: &Rect, cx: f32, cy: f32) -> (f32, f32) {
+    let (left, right) = ((cx - rect.x).abs(), (rect.x + rect.width - cx).abs());
+    let (top, bottom) = ((cy - rect.y).abs(), (rect.y + rect.height - cy).abs());
+    let (near_x, far_x) = (left.min(right), left.max(right));
+    let (near_y, far_y) = (top.min(bottom), top.max(bottom));
+
+    match (shape, size) {
+        (_, RadialSize::Px(r)) => (r, r),
+        (RadialShape::Circle, RadialSize::ClosestSide) => (near_x.min(near_y), near_x.min(near_y)),
+        (RadialShape::Circle, RadialSize::FarthestSide) => (far_x.max(far_y), far_x.max(far_y)),
+        (RadialShape::Circle, RadialSize::ClosestCorner) => {
+            let r = near_x.hypot(near_y);
+            (r, r)
+        }
+        (RadialShape::Circle, RadialSize::FarthestCorner) => {
+            let r = far_x.hypot(far_y);
+            (r, r)
+        }
+        (RadialShape::Ellipse, RadialSize::ClosestSide) => (near_x, near_y),
+        (RadialShape::Ellipse, RadialSize::FarthestSide) => (far_x, far_y),
+        // Corner ellipses keep the aspect ratio of the matching side ellipse
+        (RadialShape::Ellipse, RadialSize::ClosestCorner) => {
+            (near_x * std::f32::consts::SQRT_2, near_y * std::f32::consts::SQRT_2)
+        }
+        (RadialShape::Ellipse, RadialSize::FarthestCorner) => {
+            (far_x * std::f32::consts::SQRT_2, far_y * std::f32::consts::SQRT_2)
+        }
+    }
+}
+
+/// Resolve color stop positions to offsets in `0..=1` along a gradient of `length` pixels.
+///
+/// Missing positions default to 0 and 1 at the ends and are spread evenly
+/// in between; positions never go backwards.
+fn resolve_stops(stops: &[ColorStop], length: f32) -> Vec<(f32, &Color)> {
+    if stops.is_empty() {
+        return Vec::new();
+    }
+
+    let mut offsets: Vec<Option<f32>> = stops
+        .iter()
+        .map(|stop| {
+            stop.position.map(|pos| match pos {
+                PositionOffset::Percent(pct) => pct / 100.0,
+                PositionOffset::Px(px) if length > 0.0 => px / length,
+                PositionOffset::Px(_) => 0.0,
+            })
+        })
+        .collect();
+
+    let last = offsets.len() - 1;
+    offsets[0].get_or_insert(0.0);
+    offsets[last].get_or_insert(1.0);
+
+    let mut max = f32::MIN;
+    for offset in offsets.iter_mut().flatten() {
+        max = max.max(*offset);
+        *offset = max;
+    }
+
+    let mut start = 0;
+    for i in 1..=last {
+        if let Some(end) = offsets[i] {
+            let from = offsets[start].unwrap_or(0.0);
+            for (step, j) in (start + 1..i).enumerate() {
+                offsets[j] = Some(from + (end - from) * (step + 1) as f32 / (i - start) as f32);
+            }
+            start = i;
+        }
+    }
+
+    offsets
+        .into_iter()
+        .zip(stops)
+        .map(|(offset, stop)| (offset.unwrap_or(0.0).clamp(0.0, 1.0), &stop.color))
+        .collect()
+}
+
 fn all_same(arr: &[f32; 4]) -> bool {
     arr.iter().all(|&x| (x - arr[0]).abs() < 0.001)
 }
@@ -502,6 +1295,14 @@ fn escape_xml(s: &str) -> String {
         .replace('\'', "&apos;")
 }
 
+/// Escape a double-quoted attribute value, keeping single quotes readable.
+fn escape_attr(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -512,6 +1313,111 @@ mod tests {
         assert_eq!(escape_xml("a & b"), "a &amp; b");
     }
 
+    #[test]
+    fn test_place_image() {
+        let rect = Rect { x: 0.0, y: 0.0, width: 100.0, height: 100.0 };
+        let pos = ObjectPosition {
+            x: PositionOffset::Percent(25.0),
+            y: PositionOffset::Px(10.0),
+        };
+
+        let covered = place_image(&rect, (200.0, 100.0), true, pos);
+        assert_eq!((covered.x, covered.y, covered.width, covered.height), (-25.0, 10.0, 200.0, 100.0));
+
+        let contained = place_image(&rect, (200.0, 100.0), false, pos);
+        assert_eq!((contained.x, contained.y, contained.width, contained.height), (0.0, 10.0, 100.0, 50.0));
+    }
+
+    #[test]
+    fn test_resolve_stops() {
+        let stop = |position| ColorStop {
+            color: Color::default(),
+            position,
+        };
+        let stops = [
+            stop(None),
+            stop(None),
+            stop(Some(PositionOffset::Px(50.0))),
+            stop(Some(PositionOffset::Percent(20.0))),
+            stop(None),
+        ];
+        let offsets: Vec<f32> = resolve_stops(&stops, 100.0).into_iter().map(|(o, _)| o).collect();
+        assert_eq!(offsets, vec![0.0, 0.25, 0.5, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_gradient_dedup() {
+        let options = SvgOptions::default();
+        let mut svg = SvgBuilder::new(100.0, 100.0, &options);
+        let gradient = Gradient::parse("linear-gradient(to right, red, blue)").unwrap();
+        let rect = Rect { x: 0.0, y: 0.0, width: 100.0, height: 50.0 };
+
+        let fill = Paint::Gradient(gradient);
+        svg.render_fill_rect(&rect, &fill, &[0.0; 4]);
+        assert!(svg.output.contains("x1=\"0.00\" y1=\"25.00\" x2=\"100.00\" y2=\"25.00\""));
+
+        // Boxes of the same size share the definition wherever they are
+        let moved = Rect { x: 20.0, y: 50.0, ..rect };
+        svg.render_fill_rect(&moved, &fill, &[0.0; 4]);
+        assert_eq!(svg.output.matches("<linearGradient").count(), 1);
+        assert!(svg.output.contains(
+            r#"<rect x="0.00" y="0.00" width="100.00" height="50.00" transform="translate(20.00 50.00)" fill="url(#grad-0)"/>"#
+        ));
+
+        let taller = Rect { height: 100.0, ..rect };
+        svg.render_fill_rect(&taller, &fill, &[0.0; 4]);
+        assert_eq!(svg.output.matches("<linearGradient").count(), 2);
+    }
+
+    #[test]
+    fn test_spread_radii() {
+        assert_eq!(spread_radii(&[8.0, 0.0, 4.0, 2.0], 3.0), [11.0, 0.0, 7.0, 5.0]);
+        assert_eq!(spread_radii(&[8.0, 0.0, 4.0, 2.0], -3.0), [5.0, 0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_wavy_path() {
+        let line = DecorationLine {
+            x: 0.0,
+            y: 10.0,
+            width: 12.0,
+            thickness: 1.0,
+        };
+        // Half-waves are stretched to end exactly at the line's width
+        assert_eq!(wavy_path(&line, 2), "M0.00 10.00q2.00 -3.00 4.00 0t4.00 0t4.00 0");
+    }
+
+    #[test]
+    fn test_line_x_glyph_positions() {
+        let line = TextLineRender {
+            x: 5.0,
+            y: 20.0,
+            text: "a\u{1F600}b".to_string(),
+            char_x: vec![5.0, 12.0, 30.0],
+            spans: vec![],
+        };
+        let options = SvgOptions::default();
+        assert_eq!(SvgBuilder::new(100.0, 100.0, &options).line_x(&line), "5.00");
+
+        // The emoji takes two UTF-16 code units, so its position is repeated
+        let options = SvgOptions {
+            glyph_positions: true,
+            ..SvgOptions::default()
+        };
+        let svg = SvgBuilder::new(100.0, 100.0, &options);
+        assert_eq!(svg.line_x(&line), "5.00 12.00 12.00 30.00");
+    }
+
+    #[test]
+    fn test_border_wedge() {
+        let rect = Rect { x: 0.0, y: 0.0, width: 100.0, height: 40.0 };
+        let widths = [2.0, 4.0, 2.0, 0.0];
+        assert_eq!(border_wedge(&rect, widths, 0, 0), "0,0 100,0 80,10 0,20");
+        assert_eq!(border_wedge(&rect, widths, 1, 0), "100,0 100,40 80,30 80,10");
+        // A zero-width side owns no area
+        assert_eq!(border_wedge(&rect, widths, 3, 0), "0,40 0,0 0,20 0,20");
+    }
+
     #[test]
     fn test_all_same() {
         assert!(all_same(&[5.0, 5.0, 5.0, 5.0]));
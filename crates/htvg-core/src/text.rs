@@ -1,24 +1,35 @@
 //! Text layout and measurement using Parley.
 //!
-//! Handles text shaping, line breaking, and glyph positioning.
-//!
-//! TODO: Support font rasterization — render text glyphs to path data so SVG
-//! output is fully self-contained and doesn't depend on the viewer having the
-//! font installed. This would use the glyph outlines from the font file to
-//! emit `<path>` elements instead of `<text>` elements.
-
-use parley::layout::{Alignment, Layout, PositionedLayoutItem};
-use parley::style::{FontStack, FontWeight, LineHeight, StyleProperty};
-use parley::{AlignmentOptions, FontContext, LayoutContext};
+//! Handles text shaping, line breaking, and glyph positioning. Laid out text
+//! can also be converted to SVG path data from the glyph outlines of the
+//! resolved fonts, so vector output doesn't depend on the viewer having the
+//! font installed.
+
+use parley::fontique::{Blob, FontInfoOverride, GenericFamily};
+use parley::layout::{Alignment, Layout, Line, PositionedLayoutItem};
+use parley::style::{FontFamily, FontStack, FontWeight, FontWidth, LineHeight, StyleProperty};
+use parley::{AlignmentOptions, FontContext, FontData, LayoutContext, RangedBuilder};
+use serde::Serialize;
 use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+use std::ops::Range;
 use taffy::Size;
 
-use crate::element::TextAlign;
+use crate::element::{Direction, FontStretch, FontStyle, TextAlign, TextOverflow, WhiteSpace};
+use crate::layout::{TextContext, TextSpanResolved, TextStyleResolved};
+use crate::FontSource;
 
 /// Text layout engine wrapping Parley.
 pub struct TextLayoutEngine {
     font_cx: FontContext,
     layout_cx: LayoutContext<[u8; 4]>,
+    /// Glyph IDs shaped by `layout`, keyed by font data blob ID.
+    used_glyphs: HashMap<u64, BTreeSet<u32>>,
+    /// Family names of fonts, keyed by font data blob ID and index.
+    family_names: HashMap<(u64, u32), String>,
+    /// Runs laid out by `layout`, with the fonts they resolved to.
+    font_runs: Vec<FontRun>,
 }
 
 impl TextLayoutEngine {
@@ -26,11 +37,14 @@ impl TextLayoutEngine {
         Self {
             font_cx: FontContext::new(),
             layout_cx: LayoutContext::new(),
+            used_glyphs: HashMap::new(),
+            family_names: HashMap::new(),
+            font_runs: Vec::new(),
         }
     }
 
     /// Register a font from binary data. Returns registered family names.
-    pub fn register_font(&mut self, data: Vec<u8>) -> Vec<String> {
+    pub fn register_font(&mut self, data: impl Into<Blob<u8>>) -> Vec<String> {
         let families = self.font_cx.collection.register_fonts(data.into(), None);
         families
             .iter()
@@ -44,94 +58,234 @@ impl TextLayoutEngine {
             .collect()
     }
 
-    /// Measure text and return (width, height).
-    pub fn measure(&mut self, text: &str, font_size: f32, max_width: Option<f32>) -> Size<f32> {
-        if text.is_empty() {
-            return Size {
-                width: 0.0,
-                height: font_size * 1.2,
-            };
+    /// Register font data under the family, weight, style and stretch of
+    /// `source` rather than the ones named in the font, so it is matched the
+    /// same way as the `@font-face` rule emitted for it.
+    pub fn register_font_source(
+        &mut self,
+        data: impl Into<Blob<u8>>,
+        source: &FontSource,
+    ) -> Vec<String> {
+        let info = FontInfoOverride {
+            family_name: Some(&source.family),
+            width: Some(parley_font_width(source.stretch)),
+            style: Some(parley_font_style(source.style)),
+            weight: Some(FontWeight::new(source.weight as f32)),
+            axes: None,
+        };
+        let data = data.into();
+        for index in 0..ttf_parser::fonts_in_collection(data.as_ref()).unwrap_or(1) {
+            self.family_names.insert((data.id(), index), source.family.clone());
         }
+        let families = self.font_cx.collection.register_fonts(data, Some(info));
+        families
+            .iter()
+            .map(|(id, _info)| {
+                self.font_cx
+                    .collection
+                    .family_name(*id)
+                    .unwrap_or("unknown")
+                    .to_string()
+            })
+            .collect()
+    }
 
-        let mut builder = self
-            .layout_cx
-            .ranged_builder(&mut self.font_cx, text, 1.0, false);
-
-        builder.push_default(StyleProperty::FontSize(font_size));
-        builder.push_default(StyleProperty::LineHeight(LineHeight::FontSizeRelative(1.2)));
+    /// Glyph IDs from the given font data blob that have been laid out so far.
+    pub fn used_glyphs(&self, blob_id: u64) -> Option<&BTreeSet<u32>> {
+        self.used_glyphs.get(&blob_id)
+    }
 
-        let mut layout: Layout<[u8; 4]> = builder.build(text);
-        layout.break_all_lines(max_width);
+    /// Runs of text laid out so far, in order, with the font each resolved to.
+    pub fn font_runs(&self) -> &[FontRun] {
+        &self.font_runs
+    }
 
-        let width = layout.width();
-        let height = layout.height();
+    /// Family name of a font: the one it was registered under, or else the
+    /// one in its `name` table.
+    fn family_name(&mut self, font: &FontData) -> String {
+        self.family_names
+            .entry((font.data.id(), font.index))
+            .or_insert_with(|| {
+                let face = ttf_parser::Face::parse(font.data.as_ref(), font.index).ok();
+                face.and_then(|face| font_family_name(&face))
+                    .unwrap_or_else(|| "unknown".to_string())
+            })
+            .clone()
+    }
 
-        // Fallback: if Parley returns zero dimensions (no font available),
-        // use approximate character-width estimation.
-        if width == 0.0 || height == 0.0 {
-            return fallback_measure(text, font_size, 1.2, max_width);
-        }
+    /// Measure text and return (width, height).
+    ///
+    /// Runs the same style-aware layout as rendering, so measured boxes match
+    /// the rendered text.
+    pub fn measure(&mut self, ctx: &TextContext, max_width: Option<f32>) -> Size<f32> {
+        self.measure_lines(ctx, max_width).0
+    }
 
-        Size { width, height }
+    /// Measure text and also return the number of visible lines.
+    pub fn measure_lines(
+        &mut self,
+        ctx: &TextContext,
+        max_width: Option<f32>,
+    ) -> (Size<f32>, usize) {
+        let shaped = self.shape_text(&ctx.content, &ctx.style, &ctx.spans, max_width);
+        let size = Size {
+            width: shaped.width,
+            height: shaped.height,
+        };
+        (size, shaped.line_count)
     }
 
-    /// Lay out text with full glyph positions.
+    /// Lay out text with full glyph positions, wrapping at `max_width` (no
+    /// wrapping if `None`).
     pub fn layout(
         &mut self,
         text: &str,
-        font_family: &str,
-        font_size: f32,
-        font_weight: u16,
-        line_height: f32,
-        text_align: TextAlign,
-        max_width: f32,
+        style: &TextStyleResolved,
+        spans: &[TextSpanResolved],
+        max_width: Option<f32>,
     ) -> TextLayoutResult {
+        let mut shaped = self.shape_text(text, style, spans, max_width);
+        self.layout_shaped(&mut shaped, style, max_width)
+    }
+
+    /// Shape text and break it into lines at `max_width`, measuring it
+    /// without positioning any glyphs yet.
+    pub fn shape_text(
+        &mut self,
+        text: &str,
+        style: &TextStyleResolved,
+        spans: &[TextSpanResolved],
+        max_width: Option<f32>,
+    ) -> ShapedText {
+        let mut shaped = ShapedText {
+            text: text.to_string(),
+            spans: spans.to_vec(),
+            direction_mark: 0,
+            layout: None,
+            max_width,
+            content_width: 0.0,
+            width: 0.0,
+            height: style.font_size * style.line_height,
+            line_count: 0,
+        };
         if text.is_empty() {
-            return TextLayoutResult {
-                width: 0.0,
-                height: font_size * line_height,
-                lines: vec![],
+            return shaped;
+        }
+
+        let mut shaped_lines = self.shape(text, style, spans, max_width);
+
+        // Parley takes the base direction from the first strong character; a
+        // leading mark gives the text the direction of its style instead. The
+        // mark is only shaped, never output.
+        let mut direction_mark = 0;
+        if shaped_lines.layout.is_rtl() != (style.direction == Direction::Rtl) {
+            let (text, spans) = with_direction_mark(text, spans, style.direction);
+            let Shaped { text, spans, layout } = self.shape(&text, style, &spans, max_width);
+            shaped_lines = Shaped {
+                text: Cow::Owned(text.into_owned()),
+                spans: Cow::Owned(spans.into_owned()),
+                layout,
             };
+            direction_mark = DIRECTION_MARK_LEN;
         }
+        let Shaped {
+            text: shaped_text,
+            spans: shaped_spans,
+            layout,
+        } = shaped_lines;
 
-        let mut builder = self
-            .layout_cx
-            .ranged_builder(&mut self.font_cx, text, 1.0, false);
+        // Without a font Parley produces lines but no glyph runs with text
+        let limit = line_limit(style);
+        let has_content = layout.lines().take(limit).any(|line| {
+            line.items().any(|item| match item {
+                PositionedLayoutItem::GlyphRun(run) => {
+                    !shaped_text[run.run().text_range()].trim_end().is_empty()
+                }
+                _ => false,
+            })
+        });
+        if !has_content {
+            let fallback = fallback_layout(text, style, max_width, spans);
+            shaped.content_width = fallback.width;
+            shaped.width = fallback.width;
+            shaped.height = fallback.height;
+            shaped.line_count = fallback.lines.len();
+            return shaped;
+        }
 
-        builder.push_default(StyleProperty::FontSize(font_size));
-        builder.push_default(StyleProperty::FontWeight(FontWeight::new(font_weight as f32)));
-        builder.push_default(StyleProperty::LineHeight(LineHeight::FontSizeRelative(
-            line_height,
-        )));
-        builder.push_default(StyleProperty::FontStack(FontStack::Source(Cow::Owned(
-            font_family.to_string(),
-        ))));
+        // Lines dropped by `maxLines` don't count towards the size
+        let visible = layout.lines().take(limit);
+        let (content_width, height, line_count) =
+            visible.fold((0.0_f32, 0.0, 0), |(width, height, count), line| {
+                let m = line.metrics();
+                let width = width.max(m.advance - m.trailing_whitespace);
+                (width, height + m.line_height, count + 1)
+            });
 
-        let mut layout: Layout<[u8; 4]> = builder.build(text);
-        layout.break_all_lines(Some(max_width));
+        ShapedText {
+            text: shaped_text.into_owned(),
+            spans: shaped_spans.into_owned(),
+            direction_mark,
+            layout: Some(layout),
+            max_width,
+            content_width,
+            width: visible_width(content_width, style, max_width),
+            height,
+            line_count,
+        }
+    }
 
-        let alignment = match text_align {
-            TextAlign::Left => Alignment::Start,
+    /// Align shaped text within `max_width`, in place, and position its
+    /// glyphs.
+    ///
+    /// `max_width` may differ from the width the text was shaped at as long
+    /// as [`ShapedText::fits`] holds for it.
+    pub fn layout_shaped(
+        &mut self,
+        shaped: &mut ShapedText,
+        style: &TextStyleResolved,
+        max_width: Option<f32>,
+    ) -> TextLayoutResult {
+        let Some(layout) = &mut shaped.layout else {
+            if shaped.text.is_empty() {
+                return TextLayoutResult {
+                    width: 0.0,
+                    height: shaped.height,
+                    lines: vec![],
+                    fonts: vec![],
+                };
+            }
+            return fallback_layout(&shaped.text, style, max_width, &shaped.spans);
+        };
+        let (text, spans) = (shaped.text.as_str(), shaped.spans.as_slice());
+
+        let alignment = match style.text_align {
+            TextAlign::Start => Alignment::Start,
+            TextAlign::End => Alignment::End,
+            TextAlign::Left => Alignment::Left,
             TextAlign::Center => Alignment::Center,
-            TextAlign::Right => Alignment::End,
+            TextAlign::Right => Alignment::Right,
             TextAlign::Justify => Alignment::Justify,
         };
-        layout.align(Some(max_width), alignment, AlignmentOptions::default());
+        layout.align(max_width, alignment, AlignmentOptions::default());
 
         // Extract lines
         let mut lines = Vec::new();
+        let mut fonts: Vec<FontData> = Vec::new();
 
-        for line in layout.lines() {
+        for line in layout.lines().take(line_limit(style)) {
             let metrics = line.metrics();
             let mut line_glyphs = Vec::new();
+            let mut run_metrics = None;
             let mut line_start: Option<usize> = None;
             let mut line_end: usize = 0;
+            let mut reported: Option<Range<usize>> = None;
 
             for item in line.items() {
                 if let PositionedLayoutItem::GlyphRun(positioned_run) = item {
-                    let run_x = positioned_run.offset();
                     let run = positioned_run.run();
                     let range = run.text_range();
+                    run_metrics.get_or_insert(*run.metrics());
                     if line_start.is_none() || range.start < line_start.unwrap() {
                         line_start = Some(range.start);
                     }
@@ -139,46 +293,479 @@ impl TextLayoutEngine {
                         line_end = range.end;
                     }
 
-                    // Get glyphs from the positioned run directly
-                    for glyph in positioned_run.glyphs() {
+                    let font = run.font();
+                    let font_index = match fonts
+                        .iter()
+                        .position(|f| f.data.id() == font.data.id() && f.index == font.index)
+                    {
+                        Some(index) => index,
+                        None => {
+                            fonts.push(font.clone());
+                            fonts.len() - 1
+                        }
+                    };
+                    let skew = run.synthesis().skew().unwrap_or(0.0);
+                    let span = spans.iter().position(|s| s.range.contains(&range.start));
+
+                    // A run split into several glyph runs is reported once
+                    let run_text = text[range.start.max(shaped.direction_mark)..range.end].trim();
+                    if reported.as_ref() != Some(&range) && !run_text.is_empty() {
+                        let font_family = self.family_name(font);
+                        self.font_runs.push(FontRun {
+                            text: run_text.to_string(),
+                            font_family,
+                            missing_glyphs: positioned_run.glyphs().any(|g| g.id == 0),
+                        });
+                        reported = Some(range.clone());
+                    }
+
+                    let used = self.used_glyphs.entry(font.data.id()).or_default();
+
+                    for glyph in positioned_run.positioned_glyphs() {
+                        used.insert(glyph.id);
                         line_glyphs.push(PositionedGlyph {
                             glyph_id: glyph.id,
-                            x: run_x + glyph.x,
+                            x: glyph.x,
                             y: glyph.y,
                             advance: glyph.advance,
+                            font_index,
+                            font_size: run.font_size(),
+                            skew,
+                            span,
                         });
                     }
                 }
             }
 
-            let line_text = if let Some(start) = line_start {
-                text[start..line_end].trim_end().to_string()
+            // Lines leave out the direction mark
+            let line_range = match line_start {
+                Some(start) => {
+                    let start = start.max(shaped.direction_mark);
+                    start..start + text[start..line_end.max(start)].trim_end().len()
+                }
+                None => 0..0,
+            };
+
+            // Right-to-left lines start at their right edge
+            let (char_x, right) = char_positions(&line, text, line_range.clone());
+            let width = metrics.advance - metrics.trailing_whitespace;
+            let (line_x, left) = if layout.is_rtl() {
+                (right, right - width)
             } else {
-                String::new()
+                (metrics.offset, metrics.offset)
+            };
+
+            // Decorations follow the line's first run
+            let decoration = match run_metrics {
+                Some(run) => LineDecoration {
+                    x: left,
+                    width,
+                    underline_offset: run.underline_offset,
+                    underline_size: run.underline_size,
+                    strikethrough_offset: run.strikethrough_offset,
+                    strikethrough_size: run.strikethrough_size,
+                },
+                None => LineDecoration::default(),
             };
 
             lines.push(TextLine {
-                text: line_text,
+                text: text[line_range.clone()].to_string(),
+                x: line_x,
+                char_x,
                 baseline: metrics.baseline,
                 ascent: metrics.ascent,
                 descent: metrics.descent,
                 glyphs: line_glyphs,
+                spans: split_spans(text, line_range, spans),
+                decoration,
             });
         }
 
-        // Fallback: if Parley produced lines but none have text content
-        // (no font was available to produce glyph runs), use fallback layout.
-        let has_content = lines.iter().any(|l| !l.text.is_empty());
-        if !has_content {
-            return fallback_layout(text, font_size, line_height, max_width);
-        }
-
         TextLayoutResult {
-            width: layout.width(),
-            height: layout.height(),
+            width: visible_width(shaped.content_width, style, max_width),
+            height: shaped.height,
             lines,
+            fonts,
         }
     }
+
+    /// Build a Parley layout and break it into lines.
+    fn build(
+        &mut self,
+        text: &str,
+        style: &TextStyleResolved,
+        spans: &[TextSpanResolved],
+        max_width: Option<f32>,
+    ) -> Layout<[u8; 4]> {
+        let mut builder = self
+            .layout_cx
+            .ranged_builder(&mut self.font_cx, text, 1.0, false);
+
+        builder.push_default(StyleProperty::FontSize(style.font_size));
+        builder.push_default(StyleProperty::FontWeight(FontWeight::new(
+            style.font_weight as f32,
+        )));
+        builder.push_default(StyleProperty::FontStyle(parley_font_style(style.font_style)));
+        builder.push_default(StyleProperty::FontWidth(parley_font_width(style.font_stretch)));
+        builder.push_default(StyleProperty::LineHeight(LineHeight::FontSizeRelative(
+            style.line_height,
+        )));
+        builder.push_default(StyleProperty::FontStack(FontStack::Source(Cow::Owned(
+            style.font_family.clone(),
+        ))));
+        builder.push_default(StyleProperty::LetterSpacing(style.letter_spacing));
+        builder.push_default(StyleProperty::WordSpacing(style.word_spacing));
+
+        push_span_styles(&mut builder, spans);
+
+        let mut layout: Layout<[u8; 4]> = builder.build(text);
+        layout.break_all_lines(max_width);
+        layout
+    }
+
+    /// Lay out text honoring `whiteSpace`, `maxLines` and `textOverflow`.
+    ///
+    /// If the last visible line is cut and an ellipsis is wanted, the text
+    /// is truncated at the last cluster that fits together with the ellipsis
+    /// (measured in the font at that point) and laid out again.
+    fn shape<'t>(
+        &mut self,
+        text: &'t str,
+        style: &TextStyleResolved,
+        spans: &'t [TextSpanResolved],
+        max_width: Option<f32>,
+    ) -> Shaped<'t> {
+        let wrap_width = match style.white_space {
+            WhiteSpace::Normal => max_width,
+            WhiteSpace::Nowrap => None,
+        };
+        let layout = self.build(text, style, spans, wrap_width);
+
+        let unchanged = |layout| Shaped {
+            text: Cow::Borrowed(text),
+            spans: Cow::Borrowed(spans),
+            layout,
+        };
+        if style.text_overflow != Some(TextOverflow::Ellipsis) || layout.is_empty() {
+            return unchanged(layout);
+        }
+
+        let limit = line_limit(style);
+        let Some(last) = layout.get(layout.len().min(limit) - 1) else {
+            return unchanged(layout);
+        };
+        let max_width = max_width.unwrap_or(f32::INFINITY);
+        let line_width = last.metrics().advance - last.metrics().trailing_whitespace;
+        if layout.len() <= limit && line_width <= max_width {
+            return unchanged(layout);
+        }
+
+        // Measure the ellipsis in the style of the text it follows
+        let line_end = last.text_range().end;
+        let ellipsis_span: Vec<TextSpanResolved> = spans
+            .iter()
+            .find(|s| s.range.start < line_end && s.range.end >= line_end)
+            .map(|s| TextSpanResolved {
+                range: 0..ELLIPSIS.len(),
+                ..s.clone()
+            })
+            .into_iter()
+            .collect();
+        let ellipsis_width = self.build(ELLIPSIS, style, &ellipsis_span, None).width();
+
+        // Keep the clusters that fit in front of the ellipsis. Runs are in
+        // visual order, so walk the clusters in logical order to measure
+        // the same text that is kept
+        let available = max_width - ellipsis_width;
+        let mut clusters: Vec<(Range<usize>, f32)> = last
+            .runs()
+            .flat_map(|run| {
+                run.clusters()
+                    .map(|cluster| (cluster.text_range(), cluster.advance()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        clusters.sort_by_key(|(range, _)| range.start);
+        let mut cut = last.text_range().start;
+        let mut x = 0.0;
+        for (range, advance) in clusters {
+            x += advance;
+            if x > available + 0.01 {
+                break;
+            }
+            cut = range.end;
+        }
+
+        let (text, spans) = truncate_text(text, spans, cut);
+        let layout = self.build(&text, style, &spans, wrap_width);
+        Shaped {
+            text: Cow::Owned(text),
+            spans: Cow::Owned(spans),
+            layout,
+        }
+    }
+}
+
+/// Text shaped and broken into lines, kept so it can be aligned and
+/// positioned later without shaping it again.
+#[derive(Clone)]
+pub struct ShapedText {
+    /// Text and spans, truncated if an ellipsis was added
+    text: String,
+    spans: Vec<TextSpanResolved>,
+    /// Length of the direction mark prefixed to `text` for shaping, if any
+    direction_mark: usize,
+    /// `None` if the text is empty or no font was available
+    layout: Option<Layout<[u8; 4]>>,
+    /// Width the lines were broken at
+    max_width: Option<f32>,
+    /// Width of the widest visible line
+    content_width: f32,
+    pub width: f32,
+    pub height: f32,
+    /// Number of visible lines
+    pub line_count: usize,
+}
+
+impl ShapedText {
+    /// Whether positioning at `max_width` gives the same lines as shaping at
+    /// it would: the width is the one the text was broken at, or a narrower
+    /// one its lines still fit in.
+    pub fn fits(&self, max_width: f32) -> bool {
+        const EPSILON: f32 = 0.01;
+        let wrapped_at = self.max_width.unwrap_or(f32::INFINITY);
+        let exact = (wrapped_at - max_width).abs() < EPSILON;
+        let narrower =
+            max_width <= wrapped_at + EPSILON && self.content_width <= max_width + EPSILON;
+        exact || narrower
+    }
+}
+
+impl std::fmt::Debug for ShapedText {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShapedText")
+            .field("text", &self.text)
+            .field("max_width", &self.max_width)
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("line_count", &self.line_count)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Text laid out by `TextLayoutEngine::shape`, which may be a truncated
+/// copy of the input.
+struct Shaped<'t> {
+    text: Cow<'t, str>,
+    spans: Cow<'t, [TextSpanResolved]>,
+    layout: Layout<[u8; 4]>,
+}
+
+/// Appended to text cut by `textOverflow: ellipsis`.
+const ELLIPSIS: &str = "\u{2026}";
+
+/// Byte length of the left-to-right and right-to-left marks.
+const DIRECTION_MARK_LEN: usize = '\u{200E}'.len_utf8();
+
+/// Width of text whose widest line is `content_width`; clipped unwrapped
+/// text may shrink below its content.
+fn visible_width(content_width: f32, style: &TextStyleResolved, max_width: Option<f32>) -> f32 {
+    match max_width {
+        Some(max_width) if clips_line(style) => content_width.min(max_width),
+        _ => content_width,
+    }
+}
+
+/// Maximum number of visible lines.
+fn line_limit(style: &TextStyleResolved) -> usize {
+    style.max_lines.unwrap_or(usize::MAX).max(1)
+}
+
+/// Whether unwrapped text is clipped to the box instead of overflowing.
+pub(crate) fn clips_line(style: &TextStyleResolved) -> bool {
+    style.white_space == WhiteSpace::Nowrap && style.text_overflow == Some(TextOverflow::Clip)
+}
+
+/// Prefix text with a left-to-right or right-to-left mark, extending the
+/// first span over it.
+fn with_direction_mark(
+    text: &str,
+    spans: &[TextSpanResolved],
+    direction: Direction,
+) -> (String, Vec<TextSpanResolved>) {
+    let mark = match direction {
+        Direction::Ltr => '\u{200E}',
+        Direction::Rtl => '\u{200F}',
+    };
+    let offset = mark.len_utf8();
+    let spans = spans
+        .iter()
+        .enumerate()
+        .map(|(i, span)| TextSpanResolved {
+            range: if i == 0 { 0 } else { span.range.start + offset }..span.range.end + offset,
+            ..span.clone()
+        })
+        .collect();
+    (format!("{}{}", mark, text), spans)
+}
+
+/// Cut text at a byte offset, drop trailing whitespace and append an
+/// ellipsis styled like the span it follows.
+fn truncate_text(text: &str, spans: &[TextSpanResolved], cut: usize) -> (String, Vec<TextSpanResolved>) {
+    let kept = text[..cut].trim_end();
+    let truncated = format!("{}{}", kept, ELLIPSIS);
+
+    let mut truncated_spans: Vec<TextSpanResolved> = spans
+        .iter()
+        .filter(|s| s.range.start < kept.len())
+        .map(|s| TextSpanResolved {
+            range: s.range.start..s.range.end.min(kept.len()),
+            ..s.clone()
+        })
+        .collect();
+    match truncated_spans.last_mut() {
+        Some(last) => last.range.end = truncated.len(),
+        None => {
+            if let Some(first) = spans.first() {
+                truncated_spans.push(TextSpanResolved {
+                    range: 0..truncated.len(),
+                    ..first.clone()
+                });
+            }
+        }
+    }
+
+    (truncated, truncated_spans)
+}
+
+/// Push per-span style overrides onto a Parley builder.
+fn push_span_styles(builder: &mut RangedBuilder<'_, [u8; 4]>, spans: &[TextSpanResolved]) {
+    for span in spans {
+        let range = span.range.clone();
+        builder.push(StyleProperty::FontSize(span.font_size), range.clone());
+        builder.push(
+            StyleProperty::FontWeight(FontWeight::new(span.font_weight as f32)),
+            range.clone(),
+        );
+        builder.push(
+            StyleProperty::FontStack(FontStack::Source(Cow::Owned(span.font_family.clone()))),
+            range.clone(),
+        );
+        builder.push(
+            StyleProperty::FontStyle(parley_font_style(span.font_style)),
+            range.clone(),
+        );
+        builder.push(
+            StyleProperty::FontWidth(parley_font_width(span.font_stretch)),
+            range,
+        );
+    }
+}
+
+/// Append fallback families to a font family stack.
+pub(crate) fn font_stack_with_fallbacks(stack: String, fallbacks: &[String]) -> String {
+    if fallbacks.is_empty() {
+        return stack;
+    }
+    let fallbacks = fallbacks.iter().map(|family| match GenericFamily::parse(family) {
+        Some(_) => family.clone(),
+        None => format!("\"{}\"", family.replace('"', "")),
+    });
+    std::iter::once(stack).chain(fallbacks).collect::<Vec<_>>().join(", ")
+}
+
+/// Format a font family stack for CSS, quoting named families and ending
+/// with a generic family.
+pub(crate) fn css_font_family(stack: &str) -> String {
+    let mut families = Vec::new();
+    let mut generic = false;
+    for family in FontFamily::parse_list(stack) {
+        match family {
+            FontFamily::Named(name) => {
+                families.push(format!("'{}'", name.replace('\\', "\\\\").replace('\'', "\\'")));
+            }
+            FontFamily::Generic(family) => {
+                generic = true;
+                families.push(family.to_string());
+            }
+        }
+    }
+    if !generic {
+        families.push("sans-serif".to_string());
+    }
+    families.join(", ")
+}
+
+/// Family name from a font's `name` table, preferring the typographic family.
+fn font_family_name(face: &ttf_parser::Face) -> Option<String> {
+    let name = |id| {
+        face.names()
+            .into_iter()
+            .filter(|name| name.name_id == id)
+            .find_map(|name| name.to_string())
+    };
+    name(ttf_parser::name_id::TYPOGRAPHIC_FAMILY).or_else(|| name(ttf_parser::name_id::FAMILY))
+}
+
+pub(crate) fn parley_font_style(style: FontStyle) -> parley::style::FontStyle {
+    match style {
+        FontStyle::Normal => parley::style::FontStyle::Normal,
+        FontStyle::Italic => parley::style::FontStyle::Italic,
+        FontStyle::Oblique(angle) => parley::style::FontStyle::Oblique(Some(angle)),
+    }
+}
+
+pub(crate) fn parley_font_width(stretch: FontStretch) -> FontWidth {
+    FontWidth::from_percentage(stretch.0)
+}
+
+/// Shaped position of each character in `range`, taken from the line's
+/// clusters, along with the right edge of the characters. Characters start
+/// at their left edge, or their right edge if right-to-left, and characters
+/// inside a cluster share its position.
+fn char_positions(line: &Line<'_, [u8; 4]>, text: &str, range: Range<usize>) -> (Vec<f32>, f32) {
+    let mut clusters = Vec::new();
+    let mut x = line.metrics().offset;
+    let mut right = x;
+    for run in line.runs() {
+        for cluster in run.visual_clusters() {
+            let advance = cluster.advance();
+            let cluster_range = cluster.text_range();
+            if range.contains(&cluster_range.start) {
+                right = right.max(x + advance);
+            }
+            let start = if run.is_rtl() { x + advance } else { x };
+            clusters.push((cluster_range, start));
+            x += advance;
+        }
+    }
+    clusters.sort_by_key(|(range, _)| range.start);
+
+    let positions = text[range.clone()]
+        .char_indices()
+        .map(|(i, _)| {
+            let index = range.start + i;
+            let cluster = clusters.partition_point(|(range, _)| range.start <= index);
+            cluster.checked_sub(1).map_or(0.0, |c| clusters[c].1)
+        })
+        .collect();
+    (positions, right)
+}
+
+/// Split a line's byte range into pieces covered by each span.
+fn split_spans(text: &str, line: Range<usize>, spans: &[TextSpanResolved]) -> Vec<TextLineSpan> {
+    spans
+        .iter()
+        .enumerate()
+        .filter_map(|(index, span)| {
+            let start = span.range.start.max(line.start);
+            let end = span.range.end.min(line.end);
+            (start < end).then(|| TextLineSpan {
+                text: text[start..end].to_string(),
+                span: index,
+            })
+        })
+        .collect()
 }
 
 impl Default for TextLayoutEngine {
@@ -187,31 +774,175 @@ impl Default for TextLayoutEngine {
     }
 }
 
+/// A run of text laid out in a single font.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FontRun {
+    pub text: String,
+    /// Family name of the font the run resolved to
+    pub font_family: String,
+    /// Whether the font has no glyph for some of the characters, which then
+    /// render as boxes
+    pub missing_glyphs: bool,
+}
+
 /// Result of text layout.
 #[derive(Debug, Clone)]
 pub struct TextLayoutResult {
     pub width: f32,
     pub height: f32,
     pub lines: Vec<TextLine>,
+    /// Fonts referenced by `PositionedGlyph::font_index`.
+    pub fonts: Vec<FontData>,
+}
+
+impl TextLayoutResult {
+    /// Build SVG path data from the glyph outlines, offset by the given origin.
+    /// If `span` is set, only glyphs of that text span are included.
+    ///
+    /// Returns `None` if the layout has no shaped glyphs (e.g. no font was
+    /// available and the fallback layout was used).
+    pub fn to_path_data(&self, origin_x: f32, origin_y: f32, span: Option<usize>) -> Option<String> {
+        if self.lines.iter().all(|l| l.glyphs.is_empty()) {
+            return None;
+        }
+
+        let faces: Vec<Option<ttf_parser::Face>> = self
+            .fonts
+            .iter()
+            .map(|f| ttf_parser::Face::parse(f.data.data(), f.index).ok())
+            .collect();
+
+        let mut path = String::new();
+        let glyphs = self
+            .lines
+            .iter()
+            .flat_map(|l| &l.glyphs)
+            .filter(|g| span.is_none() || g.span == span);
+        for glyph in glyphs {
+            let Some(Some(face)) = faces.get(glyph.font_index) else {
+                continue;
+            };
+            let mut builder = GlyphPathBuilder {
+                path: &mut path,
+                scale: glyph.font_size / face.units_per_em() as f32,
+                x: origin_x + glyph.x,
+                y: origin_y + glyph.y,
+                skew: glyph.skew.to_radians().tan(),
+            };
+            face.outline_glyph(ttf_parser::GlyphId(glyph.glyph_id as u16), &mut builder);
+        }
+
+        Some(path.trim_end().to_string())
+    }
+}
+
+/// Converts font-unit glyph outlines into SVG path commands in pixel space.
+struct GlyphPathBuilder<'a> {
+    path: &'a mut String,
+    scale: f32,
+    x: f32,
+    y: f32,
+    skew: f32,
+}
+
+impl GlyphPathBuilder<'_> {
+    /// Map a point from font units (y-up) to SVG coordinates (y-down).
+    fn point(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.x + (x + y * self.skew) * self.scale,
+            self.y - y * self.scale,
+        )
+    }
+}
+
+impl ttf_parser::OutlineBuilder for GlyphPathBuilder<'_> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let (x, y) = self.point(x, y);
+        let _ = write!(self.path, "M{:.2} {:.2} ", x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let (x, y) = self.point(x, y);
+        let _ = write!(self.path, "L{:.2} {:.2} ", x, y);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (x1, y1) = self.point(x1, y1);
+        let (x, y) = self.point(x, y);
+        let _ = write!(self.path, "Q{:.2} {:.2} {:.2} {:.2} ", x1, y1, x, y);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (x1, y1) = self.point(x1, y1);
+        let (x2, y2) = self.point(x2, y2);
+        let (x, y) = self.point(x, y);
+        let _ = write!(
+            self.path,
+            "C{:.2} {:.2} {:.2} {:.2} {:.2} {:.2} ",
+            x1, y1, x2, y2, x, y
+        );
+    }
+
+    fn close(&mut self) {
+        self.path.push_str("Z ");
+    }
 }
 
 /// A line of laid out text.
 #[derive(Debug, Clone)]
 pub struct TextLine {
     pub text: String,
+    /// Where the line starts from the left of the box, set by alignment: its
+    /// left edge, or its right edge for right-to-left text.
+    pub x: f32,
+    /// Shaped x position of each character of `text`, at the edge it starts
+    /// from (empty if the text wasn't shaped).
+    pub char_x: Vec<f32>,
     pub baseline: f32,
     pub ascent: f32,
     pub descent: f32,
     pub glyphs: Vec<PositionedGlyph>,
+    /// Styled pieces of the line (empty for unstyled text).
+    pub spans: Vec<TextLineSpan>,
+    pub decoration: LineDecoration,
 }
 
-/// A positioned glyph.
+/// Extent of a line's text and the font metrics that place its decorations.
+/// Offsets are from the baseline to the top of the line, positive upwards.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LineDecoration {
+    pub x: f32,
+    pub width: f32,
+    pub underline_offset: f32,
+    pub underline_size: f32,
+    pub strikethrough_offset: f32,
+    pub strikethrough_size: f32,
+}
+
+/// A piece of a line covered by a single text span.
+#[derive(Debug, Clone)]
+pub struct TextLineSpan {
+    pub text: String,
+    /// Index into the text element's spans.
+    pub span: usize,
+}
+
+/// A positioned glyph. Coordinates are relative to the layout origin, with
+/// `y` on the baseline.
 #[derive(Debug, Clone)]
 pub struct PositionedGlyph {
     pub glyph_id: u32,
     pub x: f32,
     pub y: f32,
     pub advance: f32,
+    /// Index into `TextLayoutResult::fonts`.
+    pub font_index: usize,
+    pub font_size: f32,
+    /// Synthetic oblique angle in degrees (0 if none).
+    pub skew: f32,
+    /// Index of the text span the glyph belongs to, if any.
+    pub span: Option<usize>,
 }
 
 // ============================================================================
@@ -225,100 +956,270 @@ const ASCENT_RATIO: f32 = 0.8;
 /// Approximate descent as a fraction of font size.
 const DESCENT_RATIO: f32 = 0.2;
 
-/// Estimate text width for a string at a given font size.
-fn estimate_text_width(text: &str, font_size: f32) -> f32 {
-    text.chars().count() as f32 * font_size * CHAR_WIDTH_RATIO
+/// Approximate advance of a single character, including letter spacing.
+fn estimate_char_width(style: &TextStyleResolved) -> f32 {
+    style.font_size * CHAR_WIDTH_RATIO + style.letter_spacing
+}
+
+/// Estimate text width for a string in the given style, including letter and
+/// word spacing.
+fn estimate_text_width(text: &str, style: &TextStyleResolved) -> f32 {
+    let spaces = text.chars().filter(|&c| c == ' ').count();
+    text.chars().count() as f32 * estimate_char_width(style) + spaces as f32 * style.word_spacing
 }
 
-/// Simple word-wrap: split text into lines that fit within max_width.
-fn word_wrap(text: &str, font_size: f32, max_width: Option<f32>) -> Vec<String> {
+/// Simple word-wrap: split text into line byte ranges that fit within max_width.
+fn word_wrap(text: &str, style: &TextStyleResolved, max_width: Option<f32>) -> Vec<Range<usize>> {
     let max = max_width.unwrap_or(f32::MAX);
     let mut lines = Vec::new();
-    let mut current_line = String::new();
+    let mut current_line: Option<Range<usize>> = None;
     let mut current_width: f32 = 0.0;
-    let space_width = font_size * CHAR_WIDTH_RATIO;
+    let space_width = estimate_char_width(style) + style.word_spacing;
 
     for word in text.split_whitespace() {
-        let word_width = estimate_text_width(word, font_size);
-
-        if !current_line.is_empty() && current_width + space_width + word_width > max {
-            lines.push(current_line);
-            current_line = word.to_string();
-            current_width = word_width;
-        } else {
-            if !current_line.is_empty() {
-                current_line.push(' ');
-                current_width += space_width;
+        let start = word.as_ptr() as usize - text.as_ptr() as usize;
+        let word_range = start..start + word.len();
+        let word_width = estimate_text_width(word, style);
+
+        match current_line.as_mut() {
+            Some(line) if current_width + space_width + word_width > max => {
+                lines.push(line.clone());
+                current_line = Some(word_range);
+                current_width = word_width;
+            }
+            Some(line) => {
+                line.end = word_range.end;
+                current_width += space_width + word_width;
+            }
+            None => {
+                current_line = Some(word_range);
+                current_width = word_width;
             }
-            current_line.push_str(word);
-            current_width += word_width;
         }
     }
-    if !current_line.is_empty() {
-        lines.push(current_line);
+    if let Some(line) = current_line {
+        lines.push(line);
     }
     if lines.is_empty() {
-        lines.push(String::new());
+        lines.push(0..0);
     }
     lines
 }
 
-/// Fallback measurement when Parley returns zero dimensions.
-fn fallback_measure(
-    text: &str,
-    font_size: f32,
-    line_height: f32,
+/// Fallback line breaking honoring `whiteSpace`, `maxLines` and `textOverflow`.
+/// Returns the (possibly truncated) text, its spans and the visible lines.
+fn fallback_wrap<'t>(
+    text: &'t str,
+    style: &TextStyleResolved,
+    spans: &'t [TextSpanResolved],
     max_width: Option<f32>,
-) -> Size<f32> {
-    let wrapped = word_wrap(text, font_size, max_width);
-    let row_height = font_size * line_height;
-    let width = wrapped
-        .iter()
-        .map(|l| estimate_text_width(l, font_size))
-        .fold(0.0_f32, f32::max);
-    let width = match max_width {
-        Some(mw) => width.min(mw),
-        None => width,
+) -> (Cow<'t, str>, Cow<'t, [TextSpanResolved]>, Vec<Range<usize>>) {
+    let wrap_width = match style.white_space {
+        WhiteSpace::Normal => max_width,
+        WhiteSpace::Nowrap => None,
     };
-    Size {
-        width,
-        height: row_height * wrapped.len() as f32,
+    let limit = line_limit(style);
+
+    let mut wrapped = word_wrap(text, style, wrap_width);
+    let last = wrapped[wrapped.len().min(limit) - 1].clone();
+    let max_width = max_width.unwrap_or(f32::INFINITY);
+    let overflows = wrapped.len() > limit || estimate_text_width(&text[last.clone()], style) > max_width;
+
+    if style.text_overflow != Some(TextOverflow::Ellipsis) || !overflows {
+        wrapped.truncate(limit);
+        return (Cow::Borrowed(text), Cow::Borrowed(spans), wrapped);
     }
+
+    let available = max_width - estimate_text_width(ELLIPSIS, style);
+    let fitting = (available / estimate_char_width(style).max(1.0)).max(0.0) as usize;
+    let cut = text[last.clone()]
+        .char_indices()
+        .nth(fitting)
+        .map(|(i, _)| last.start + i)
+        .unwrap_or(last.end);
+
+    let (text, spans) = truncate_text(text, spans, cut);
+    let mut wrapped = word_wrap(&text, style, wrap_width);
+    wrapped.truncate(limit);
+    (Cow::Owned(text), Cow::Owned(spans), wrapped)
 }
 
 /// Fallback layout when Parley produces no glyph runs.
 fn fallback_layout(
     text: &str,
-    font_size: f32,
-    line_height: f32,
-    max_width: f32,
+    style: &TextStyleResolved,
+    max_width: Option<f32>,
+    spans: &[TextSpanResolved],
 ) -> TextLayoutResult {
-    let wrapped = word_wrap(text, font_size, Some(max_width));
-    let row_height = font_size * line_height;
+    let font_size = style.font_size;
+    let (text, spans, wrapped) = fallback_wrap(text, style, spans, max_width);
+    let row_height = font_size * style.line_height;
     let ascent = font_size * ASCENT_RATIO;
     let descent = font_size * DESCENT_RATIO;
 
+    let thickness = font_size / 18.0;
+
+    let width = wrapped
+        .iter()
+        .map(|l| estimate_text_width(&text[l.clone()], style))
+        .fold(0.0_f32, f32::max);
+    let width = match max_width {
+        Some(mw) if style.white_space == WhiteSpace::Normal || clips_line(style) => width.min(mw),
+        _ => width,
+    };
+
+    // Right-to-left lines start at the right edge of the box
+    let right = max_width.unwrap_or(width);
+
     let mut lines = Vec::new();
-    for (i, line_text) in wrapped.iter().enumerate() {
+    for (i, line_range) in wrapped.iter().enumerate() {
         let baseline = row_height * i as f32 + ascent;
+        let line_width = estimate_text_width(&text[line_range.clone()], style);
+        let (x, left) = match style.direction {
+            Direction::Ltr => (0.0, 0.0),
+            Direction::Rtl => (right, right - line_width),
+        };
         lines.push(TextLine {
-            text: line_text.clone(),
+            text: text[line_range.clone()].to_string(),
+            x,
+            char_x: vec![],
             baseline,
             ascent,
             descent,
             glyphs: vec![],
+            spans: split_spans(&text, line_range.clone(), &spans),
+            decoration: LineDecoration {
+                x: left,
+                width: line_width,
+                underline_offset: -descent / 2.0,
+                underline_size: thickness,
+                strikethrough_offset: ascent * 0.35 + thickness / 2.0,
+                strikethrough_size: thickness,
+            },
         });
     }
 
-    let width = wrapped
-        .iter()
-        .map(|l| estimate_text_width(l, font_size))
-        .fold(0.0_f32, f32::max)
-        .min(max_width);
-
     TextLayoutResult {
         width,
         height: row_height * wrapped.len() as f32,
         lines,
+        fonts: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(range: Range<usize>) -> TextSpanResolved {
+        TextSpanResolved {
+            range,
+            font_family: "sans-serif".to_string(),
+            font_size: 16.0,
+            font_weight: 400,
+            font_style: FontStyle::Normal,
+            font_stretch: FontStretch::NORMAL,
+            color: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_truncate_text() {
+        let spans = [span(0..6), span(6..11)];
+        let (text, truncated) = truncate_text("Hello world", &spans, 6);
+        assert_eq!(text, "Hello\u{2026}");
+        // The ellipsis extends the span it follows
+        assert_eq!(truncated.len(), 1);
+        assert_eq!(truncated[0].range, 0..text.len());
+
+        let (text, truncated) = truncate_text("Hello world", &spans, 8);
+        assert_eq!(text, "Hello wo\u{2026}");
+        assert_eq!(truncated[1].range, 6..text.len());
+    }
+
+    #[test]
+    fn test_css_font_family() {
+        assert_eq!(css_font_family("Inter, \"Noto Sans JP\""), "'Inter', 'Noto Sans JP', sans-serif");
+        assert_eq!(css_font_family("Georgia, serif"), "'Georgia', serif");
+
+        let stack = font_stack_with_fallbacks("Inter".to_string(), &["Noto Sans JP".to_string(), "emoji".to_string()]);
+        assert_eq!(stack, "Inter, \"Noto Sans JP\", emoji");
+        assert_eq!(css_font_family(&stack), "'Inter', 'Noto Sans JP', emoji");
+    }
+
+    #[test]
+    fn test_with_direction_mark() {
+        let spans = [span(0..6), span(6..11)];
+        let (text, marked) = with_direction_mark("Hello world", &spans, Direction::Rtl);
+        assert_eq!(text, "\u{200F}Hello world");
+        // The first span covers the mark so spans still span the whole text
+        assert_eq!(marked[0].range, 0..9);
+        assert_eq!(marked[1].range, 9..14);
+    }
+
+    #[test]
+    fn test_fallback_wrap() {
+        let style = TextStyleResolved {
+            font_size: 10.0,
+            max_lines: Some(2),
+            text_overflow: Some(TextOverflow::Ellipsis),
+            ..Default::default()
+        };
+        let text = "aaaa bbbb cccc dddd";
+        let (truncated, _, lines) = fallback_wrap(text, &style, &[], Some(30.0));
+        assert_eq!(lines.len(), 2);
+        assert_eq!(&truncated[lines[1].clone()], "bbbb\u{2026}");
+
+        let nowrap = TextStyleResolved {
+            white_space: WhiteSpace::Nowrap,
+            ..style.clone()
+        };
+        let (truncated, _, lines) = fallback_wrap(text, &nowrap, &[], Some(30.0));
+        assert_eq!(lines.len(), 1);
+        assert_eq!(truncated, "aaaa\u{2026}");
+
+        // Without textOverflow extra lines are just dropped
+        let clip = TextStyleResolved {
+            text_overflow: None,
+            ..style
+        };
+        let (truncated, _, lines) = fallback_wrap(text, &clip, &[], Some(30.0));
+        assert_eq!(&truncated[lines[1].clone()], "bbbb");
+    }
+
+    #[test]
+    fn test_fallback_layout_rtl() {
+        let style = TextStyleResolved {
+            font_size: 10.0,
+            direction: Direction::Rtl,
+            ..Default::default()
+        };
+        // Wrapped lines are ragged on the left, starting at the box's right edge
+        let layout = fallback_layout("aaaa bb", &style, Some(30.0), &[]);
+        assert_eq!(layout.lines.len(), 2);
+        for line in &layout.lines {
+            assert_eq!(line.x, 30.0);
+            assert_eq!(line.decoration.x + line.decoration.width, 30.0);
+        }
+    }
+
+    #[test]
+    fn test_estimate_text_width_spacing() {
+        let style = TextStyleResolved {
+            font_size: 10.0,
+            letter_spacing: 2.0,
+            word_spacing: 5.0,
+            ..Default::default()
+        };
+        // Three characters at 5.5 + 2 each, plus one space's word spacing
+        assert_eq!(estimate_text_width("a b", &style), 27.5);
+
+        // Spacing makes words wrap sooner
+        let unspaced = TextStyleResolved {
+            font_size: 10.0,
+            ..Default::default()
+        };
+        assert_eq!(word_wrap("aaa bbb", &unspaced, Some(40.0)).len(), 1);
+        assert_eq!(word_wrap("aaa bbb", &style, Some(40.0)).len(), 2);
     }
 }
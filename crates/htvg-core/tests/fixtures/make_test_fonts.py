@@ -0,0 +1,185 @@
+#!/usr/bin/env python3
+"""Generate the minimal TrueType fonts used by the htvg-core tests.
+
+Every glyph is the same ring-shaped outline, so the fonts are tiny, freely
+redistributable and shape deterministically on any machine.
+
+    python3 make_test_fonts.py
+"""
+
+import os
+import struct
+
+UNITS_PER_EM = 1000
+ASCENT = 800
+DESCENT = -200
+
+
+def ring(advance):
+    """Outline of an octagonal ring filling the glyph's advance."""
+    def octagon(x0, y0, x1, y1, cut):
+        points = [
+            (x0 + cut, y0), (x0, y0 + cut), (x0, y1 - cut), (x0 + cut, y1),
+            (x1 - cut, y1), (x1, y1 - cut), (x1, y0 + cut), (x1 - cut, y0),
+        ]
+        return points
+
+    x0, x1 = 50, advance - 50
+    outer = octagon(x0, 0, x1, 700, 60)
+    inner = list(reversed(octagon(x0 + 80, 80, x1 - 80, 620, 30)))
+    return [outer, inner]
+
+
+def glyph_data(contours):
+    if not contours:
+        return b""
+    points = [p for contour in contours for p in contour]
+    xs = [x for x, _ in points]
+    ys = [y for _, y in points]
+    out = struct.pack(">hhhhh", len(contours), min(xs), min(ys), max(xs), max(ys))
+    end = -1
+    for contour in contours:
+        end += len(contour)
+        out += struct.pack(">H", end)
+    out += struct.pack(">H", 0)
+    out += bytes([1] * len(points))
+    prev = 0
+    for x in xs:
+        out += struct.pack(">h", x - prev)
+        prev = x
+    prev = 0
+    for y in ys:
+        out += struct.pack(">h", y - prev)
+        prev = y
+    return out
+
+
+def cmap_table(mapping):
+    codes = sorted(mapping)
+    segments = [(c, c, mapping[c]) for c in codes] + [(0xFFFF, 0xFFFF, 0)]
+    seg_count = len(segments)
+    entry_selector = seg_count.bit_length() - 1
+    search_range = 2 * (1 << entry_selector)
+    sub = struct.pack(">HHHH", seg_count * 2, search_range, entry_selector, seg_count * 2 - search_range)
+    sub += b"".join(struct.pack(">H", end) for _, end, _ in segments)
+    sub += struct.pack(">H", 0)
+    sub += b"".join(struct.pack(">H", start) for start, _, _ in segments)
+    sub += b"".join(struct.pack(">H", ((gid - start) if gid else 1) % 0x10000) for start, _, gid in segments)
+    sub += b"".join(struct.pack(">H", 0) for _ in segments)
+    sub = struct.pack(">HHH", 4, 6 + len(sub), 0) + sub
+    return struct.pack(">HHHHI", 0, 1, 3, 1, 12) + sub
+
+
+def name_table(family):
+    records = [(1, family), (2, "Regular"), (4, family), (6, family.replace(" ", ""))]
+    strings = b""
+    entries = b""
+    for name_id, value in records:
+        encoded = value.encode("utf-16-be")
+        entries += struct.pack(">HHHHHH", 3, 1, 0x409, name_id, len(encoded), len(strings))
+        strings += encoded
+    return struct.pack(">HHH", 0, len(records), 6 + len(entries)) + entries + strings
+
+
+def checksum(data):
+    data += b"\0" * (-len(data) % 4)
+    return sum(struct.unpack(">%dI" % (len(data) // 4), data)) & 0xFFFFFFFF
+
+
+def build_font(family, chars):
+    """A font mapping each of `chars` (character, advance) to a ring glyph."""
+    glyphs = [(600, glyph_data(ring(600)))]
+    mapping = {}
+    for char, advance in chars:
+        mapping[ord(char)] = len(glyphs)
+        contours = [] if char.isspace() else ring(advance)
+        glyphs.append((advance, glyph_data(contours)))
+
+    glyf = b""
+    loca = b""
+    for _, data in glyphs:
+        loca += struct.pack(">I", len(glyf))
+        glyf += data + b"\0" * (-len(data) % 4)
+    loca += struct.pack(">I", len(glyf))
+
+    num_glyphs = len(glyphs)
+    max_advance = max(advance for advance, _ in glyphs)
+    first_char = min(mapping)
+    last_char = min(max(mapping), 0xFFFF)
+
+    tables = {
+        b"head": struct.pack(
+            ">IIIIHHqqhhhhHHhhh",
+            0x00010000, 0x00010000, 0, 0x5F0F3CF5, 0x000B, UNITS_PER_EM,
+            0, 0, 0, DESCENT, max_advance, ASCENT, 0, 8, 2, 1, 0,
+        ),
+        b"hhea": struct.pack(
+            ">IhhhHhhhhhhhhhhhH",
+            0x00010000, ASCENT, DESCENT, 0, max_advance, 0, 0, max_advance,
+            1, 0, 0, 0, 0, 0, 0, 0, num_glyphs,
+        ),
+        b"maxp": struct.pack(">IHHHHHHHHHHHHHH", 0x00010000, num_glyphs, 16, 2, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0),
+        b"OS/2": struct.pack(
+            ">HhHHHhhhhhhhhhhh10sIIII4sHHHhhhHHIIhhHHH",
+            4, 600, 400, 5, 0, 650, 600, 0, 75, 650, 600, 0, 350, 50, 300, 0,
+            bytes(10), 0, 0, 0, 0, b"NONE", 0x40, first_char, last_char,
+            ASCENT, DESCENT, 0, ASCENT, -DESCENT, 1, 0, 500, 700, 0, 32, 0,
+        ),
+        b"hmtx": b"".join(struct.pack(">Hh", advance, 50) for advance, _ in glyphs),
+        b"cmap": cmap_table(mapping),
+        b"loca": loca,
+        b"glyf": glyf,
+        b"name": name_table(family),
+        b"post": struct.pack(">IIhhIIIII", 0x00030000, 0, -100, 50, 0, 0, 0, 0, 0),
+    }
+
+    tags = sorted(tables)
+    num_tables = len(tags)
+    entry_selector = num_tables.bit_length() - 1
+    search_range = (1 << entry_selector) * 16
+    out = struct.pack(">IHHHH", 0x00010000, num_tables, search_range, entry_selector, num_tables * 16 - search_range)
+    offset = 12 + 16 * num_tables
+    body = b""
+    for tag in tags:
+        data = tables[tag]
+        out += struct.pack(">4sIII", tag, checksum(data), offset + len(body), len(data))
+        body += data + b"\0" * (-len(data) % 4)
+    font = bytearray(out + body)
+
+    head = offset + sum(len(tables[t]) + (-len(tables[t]) % 4) for t in tags[: tags.index(b"head")])
+    struct.pack_into(">I", font, head + 8, (0xB1B0AFBA - checksum(bytes(font))) & 0xFFFFFFFF)
+    return bytes(font)
+
+
+def latin_chars():
+    narrow = set("iljtfI.,:;'!|")
+    wide = set("mwMW@")
+    for code in range(0x20, 0x7F):
+        char = chr(code)
+        if char == " ":
+            advance = 250
+        elif char in narrow:
+            advance = 350
+        elif char in wide:
+            advance = 850
+        else:
+            advance = 600
+        yield char, advance
+
+
+def cjk_chars():
+    for char in "山田中日本語":
+        yield char, 1000
+
+
+FONTS = {
+    "test-sans.ttf": ("HTVG Test Sans", list(latin_chars())),
+    "test-cjk.ttf": ("HTVG Test CJK", list(cjk_chars())),
+}
+
+
+if __name__ == "__main__":
+    here = os.path.dirname(os.path.abspath(__file__))
+    for file_name, (family, chars) in FONTS.items():
+        with open(os.path.join(here, file_name), "wb") as f:
+            f.write(build_font(family, chars))
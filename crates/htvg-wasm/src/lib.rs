@@ -12,7 +12,7 @@ pub fn init() {
 /// Compile a self-contained HTVG document JSON to SVG.
 ///
 /// Input: `{ "meta": { "width": 800 }, "content": { "type": "flex", ... } }`
-/// Returns: `{ "svg": "...", "width": 800, "height": 600, "warnings": [] }`
+/// Returns: `{ "svg": "...", "width": 800, "height": 600, "fontRuns": [], "warnings": [] }`
 #[wasm_bindgen(js_name = "compileDocument")]
 pub fn compile_document_wasm(doc_json: &str) -> Result<JsValue, JsValue> {
     let result = compile_document(doc_json).map_err(|e| {
@@ -27,7 +27,7 @@ pub fn compile_document_wasm(doc_json: &str) -> Result<JsValue, JsValue> {
 ///
 /// `element_json`: `{ "type": "flex", "children": [...] }`
 /// `options_json`: `{ "width": 800 }`
-/// Returns: `{ "svg": "...", "width": 800, "height": 600, "warnings": [] }`
+/// Returns: `{ "svg": "...", "width": 800, "height": 600, "fontRuns": [], "warnings": [] }`
 #[wasm_bindgen(js_name = "compile")]
 pub fn compile_wasm(element_json: &str, options_json: &str) -> Result<JsValue, JsValue> {
     let options: CompileOptions = serde_json::from_str(options_json)
@@ -3,21 +3,70 @@
 // These are the JSON schema types for HTVG documents.
 // ============================================================================
 
-/** A dimension value: pixels (number) or percentage (string like "50%"). */
+/**
+ * A length: pixels (number) or a string with a px, %, em, rem, vw or vh unit,
+ * or a calc() expression (e.g. "calc(100% - 2em)"). rem is relative to the
+ * root fontSize, vw/vh to the output size.
+ */
 export type Dimension = number | string;
 
-/** Spacing: uniform (number) or multi-value string ("10 20" or "10 20 30 40"). */
+/** Spacing: uniform (number) or 1-4 space-separated lengths ("10 20" or "1em 2em 0 10%"). */
 export type Spacing = number | string;
 
-/** Border radius: uniform (number) or per-corner string ("8 8 0 0"). */
+/** Margin: like Spacing, but sides can be "auto" (e.g. "0 auto"). */
+export type Margin = number | string;
+
+/** Border radius: uniform (number) or 1-4 space-separated lengths ("8 8 0 0"); percentages are of the shorter side. */
 export type BorderRadius = number | string;
 
-/** CSS color string: hex (#RGB, #RRGGBB, #RRGGBBAA), rgb(), rgba(), or named. */
+/**
+ * CSS color string: hex (#RGB, #RGBA, #RRGGBB, #RRGGBBAA), a named color,
+ * rgb(), hsl(), hwb(), lab(), lch(), oklab(), oklch(), color(), or
+ * "currentColor" (the text color). Colors outside sRGB are gamut mapped.
+ */
 export type Color = string;
 
+/** CSS gradient: linear-gradient() or radial-gradient() (e.g. "linear-gradient(135deg, #f00, #00f 80%)"). */
+export type Gradient = string;
+
+/** CSS box-shadow list: "[inset] <x> <y> [blur] [spread] [color]", comma-separated (e.g. "0 4px 12px rgba(0,0,0,0.2)"). */
+export type BoxShadow = string;
+
+/** Border line style. */
+export type BorderStyle = "solid" | "dashed" | "dotted" | "double";
+
+/** Border side: width in pixels or "<width> <style> <color>" shorthand (e.g. "1px dashed #ccc"). */
+export type Border = number | string;
+
+/** CSS transform function list: translate(), scale(), rotate(), skew(), matrix() and their X/Y forms (e.g. "rotate(12deg) scale(1.1)"). */
+export type Transform = string;
+
 /** Font weight: number (100-900) or keyword. */
 export type FontWeight = number | "normal" | "bold";
 
+/** Grid track list: px, %, fr, auto, min-content, max-content, repeat() and minmax() (e.g. "200px repeat(2, 1fr)"). */
+export type GridTemplate = string;
+
+/** Grid item placement: line number, "span N", or "start / end" (e.g. "1 / span 2"). */
+export type GridLine = number | string;
+
+/** Font style: "normal", "italic" or "oblique [angle]" (e.g. "oblique 10deg"). */
+export type FontStyle = "normal" | "italic" | "oblique" | `oblique ${string}`;
+
+/** Font width: a keyword, a percentage string (e.g. "75%") or a percentage number. */
+export type FontStretch =
+  | "ultra-condensed"
+  | "extra-condensed"
+  | "condensed"
+  | "semi-condensed"
+  | "normal"
+  | "semi-expanded"
+  | "expanded"
+  | "extra-expanded"
+  | "ultra-expanded"
+  | `${number}%`
+  | number;
+
 // ============================================================================
 // Enum types
 // ============================================================================
@@ -29,6 +78,19 @@ export type JustifyContent =
   | "center"
   | "space-between"
   | "space-around"
+  | "space-evenly"
+  | "stretch";
+/** How wrapped flex lines are distributed along the cross axis. */
+export type AlignContent =
+  | "normal"
+  | "start"
+  | "end"
+  | "flex-start"
+  | "flex-end"
+  | "center"
+  | "stretch"
+  | "space-between"
+  | "space-around"
   | "space-evenly";
 export type AlignItems =
   | "flex-start"
@@ -36,9 +98,19 @@ export type AlignItems =
   | "center"
   | "stretch"
   | "baseline";
-export type FlexWrap = "nowrap" | "wrap";
-export type TextAlign = "left" | "center" | "right" | "justify";
+export type FlexWrap = "nowrap" | "wrap" | "wrap-reverse";
+export type GridAutoFlow = "row" | "column" | "row dense" | "column dense";
+export type TextAlign = "start" | "end" | "left" | "center" | "right" | "justify";
+export type Direction = "ltr" | "rtl";
+export type TextOverflow = "clip" | "ellipsis";
+export type WhiteSpace = "normal" | "nowrap";
+export type FontSizeMode = "fixed" | "fit";
+/** "none" or a space-separated list, e.g. "underline line-through" */
+export type TextDecorationLine = string;
+export type TextDecorationStyle = "solid" | "double" | "dotted" | "dashed" | "wavy";
 export type ObjectFit = "contain" | "cover" | "fill";
+export type Position = "static" | "relative" | "absolute";
+export type Overflow = "visible" | "hidden";
 
 // ============================================================================
 // Style types
@@ -47,6 +119,16 @@ export type ObjectFit = "contain" | "cover" | "fill";
 /** Style for Box elements (block container). */
 export interface BoxStyle {
   display?: "block" | "flex" | "none";
+  /** Inherited by children (default "ltr") */
+  direction?: Direction;
+  /** Text properties inherited by descendant text; also the currentColor of this element */
+  color?: Color;
+  fontFamily?: string;
+  fontSize?: number;
+  fontWeight?: FontWeight;
+  lineHeight?: number;
+  textAlign?: TextAlign;
+  letterSpacing?: number;
 
   width?: Dimension;
   height?: Dimension;
@@ -55,19 +137,58 @@ export interface BoxStyle {
   minHeight?: Dimension;
   maxHeight?: Dimension;
 
-  margin?: Spacing;
+  margin?: Margin;
   padding?: Spacing;
 
   backgroundColor?: Color;
+  backgroundImage?: Gradient;
+  boxShadow?: BoxShadow;
   borderWidth?: number;
   borderColor?: Color;
+  borderStyle?: BorderStyle;
+  borderTop?: Border;
+  borderRight?: Border;
+  borderBottom?: Border;
+  borderLeft?: Border;
   borderRadius?: BorderRadius;
   opacity?: number;
+  overflow?: Overflow;
+
+  flexGrow?: number;
+  flexShrink?: number;
+  /** Initial main size: a Dimension or "auto" */
+  flexBasis?: Dimension;
+  /** Overrides the parent's alignItems */
+  alignSelf?: AlignItems;
+  /** Layout and paint position among flex/grid siblings (default 0) */
+  order?: number;
+
+  position?: Position;
+  top?: Dimension;
+  right?: Dimension;
+  bottom?: Dimension;
+  left?: Dimension;
+  zIndex?: number;
+  transform?: Transform;
+  transformOrigin?: string;
+
+  gridColumn?: GridLine;
+  gridRow?: GridLine;
 }
 
 /** Style for Flex elements (flex container). */
 export interface FlexStyle {
   display?: "block" | "flex" | "none";
+  /** Inherited by children (default "ltr") */
+  direction?: Direction;
+  /** Text properties inherited by descendant text; also the currentColor of this element */
+  color?: Color;
+  fontFamily?: string;
+  fontSize?: number;
+  fontWeight?: FontWeight;
+  lineHeight?: number;
+  textAlign?: TextAlign;
+  letterSpacing?: number;
 
   width?: Dimension;
   height?: Dimension;
@@ -76,20 +197,119 @@ export interface FlexStyle {
   minHeight?: Dimension;
   maxHeight?: Dimension;
 
-  margin?: Spacing;
+  margin?: Margin;
   padding?: Spacing;
 
   flexDirection?: FlexDirection;
   justifyContent?: JustifyContent;
   alignItems?: AlignItems;
-  gap?: number;
+  alignContent?: AlignContent;
+  gap?: Dimension;
+  rowGap?: Dimension;
+  columnGap?: Dimension;
   flexWrap?: FlexWrap;
 
   backgroundColor?: Color;
+  backgroundImage?: Gradient;
+  boxShadow?: BoxShadow;
+  borderWidth?: number;
+  borderColor?: Color;
+  borderStyle?: BorderStyle;
+  borderTop?: Border;
+  borderRight?: Border;
+  borderBottom?: Border;
+  borderLeft?: Border;
+  borderRadius?: BorderRadius;
+  opacity?: number;
+  overflow?: Overflow;
+
+  flexGrow?: number;
+  flexShrink?: number;
+  /** Initial main size: a Dimension or "auto" */
+  flexBasis?: Dimension;
+  /** Overrides the parent's alignItems */
+  alignSelf?: AlignItems;
+  /** Layout and paint position among flex/grid siblings (default 0) */
+  order?: number;
+
+  position?: Position;
+  top?: Dimension;
+  right?: Dimension;
+  bottom?: Dimension;
+  left?: Dimension;
+  zIndex?: number;
+  transform?: Transform;
+  transformOrigin?: string;
+
+  gridColumn?: GridLine;
+  gridRow?: GridLine;
+}
+
+/** Style for Grid elements (grid container). */
+export interface GridStyle {
+  display?: "block" | "flex" | "none";
+  /** Inherited by children (default "ltr") */
+  direction?: Direction;
+  /** Text properties inherited by descendant text; also the currentColor of this element */
+  color?: Color;
+  fontFamily?: string;
+  fontSize?: number;
+  fontWeight?: FontWeight;
+  lineHeight?: number;
+  textAlign?: TextAlign;
+  letterSpacing?: number;
+
+  width?: Dimension;
+  height?: Dimension;
+  minWidth?: Dimension;
+  maxWidth?: Dimension;
+  minHeight?: Dimension;
+  maxHeight?: Dimension;
+
+  margin?: Margin;
+  padding?: Spacing;
+
+  gridTemplateColumns?: GridTemplate;
+  gridTemplateRows?: GridTemplate;
+  gridAutoFlow?: GridAutoFlow;
+  gap?: Dimension;
+  rowGap?: Dimension;
+  columnGap?: Dimension;
+
+  backgroundColor?: Color;
+  backgroundImage?: Gradient;
+  boxShadow?: BoxShadow;
   borderWidth?: number;
   borderColor?: Color;
+  borderStyle?: BorderStyle;
+  borderTop?: Border;
+  borderRight?: Border;
+  borderBottom?: Border;
+  borderLeft?: Border;
   borderRadius?: BorderRadius;
   opacity?: number;
+  overflow?: Overflow;
+
+  flexGrow?: number;
+  flexShrink?: number;
+  /** Initial main size: a Dimension or "auto" */
+  flexBasis?: Dimension;
+  /** Overrides the parent's alignItems */
+  alignSelf?: AlignItems;
+  /** Layout and paint position among flex/grid siblings (default 0) */
+  order?: number;
+
+  position?: Position;
+  top?: Dimension;
+  right?: Dimension;
+  bottom?: Dimension;
+  left?: Dimension;
+  zIndex?: number;
+  transform?: Transform;
+  transformOrigin?: string;
+
+  gridColumn?: GridLine;
+  gridRow?: GridLine;
 }
 
 /** Style for Text elements. */
@@ -97,14 +317,68 @@ export interface TextStyle {
   fontFamily?: string;
   fontSize?: number;
   fontWeight?: FontWeight;
+  fontStyle?: FontStyle;
+  fontStretch?: FontStretch;
   lineHeight?: number;
   textAlign?: TextAlign;
+  direction?: Direction;
   color?: Color;
+  /** Extra space after each character (pixels) */
   letterSpacing?: number;
+  /** Extra space after each word separator (pixels) */
+  wordSpacing?: number;
   textRendering?: "text" | "vector";
+  /** Any of "underline", "overline" and "line-through" */
+  textDecorationLine?: TextDecorationLine;
+  /** Defaults to the text color */
+  textDecorationColor?: Color;
+  /** Line thickness in pixels; the font's underline/strikeout size if unset */
+  textDecorationThickness?: number;
+  textDecorationStyle?: TextDecorationStyle;
+  /** "fit" picks the largest size between minFontSize and maxFontSize that fits the box */
+  fontSizeMode?: FontSizeMode;
+  /** Smallest fitted size (default 1) */
+  minFontSize?: number;
+  /** Largest fitted size (defaults to fontSize) */
+  maxFontSize?: number;
+  /** Maximum number of visible lines */
+  maxLines?: number;
+  /** How text cut by maxLines or whiteSpace "nowrap" ends; overflows visibly if unset */
+  textOverflow?: TextOverflow;
+  whiteSpace?: WhiteSpace;
+
+  margin?: Margin;
 
   flexGrow?: number;
   flexShrink?: number;
+  /** Initial main size: a Dimension or "auto" */
+  flexBasis?: Dimension;
+  /** Overrides the parent's alignItems */
+  alignSelf?: AlignItems;
+  /** Layout and paint position among flex/grid siblings (default 0) */
+  order?: number;
+
+  position?: Position;
+  top?: Dimension;
+  right?: Dimension;
+  bottom?: Dimension;
+  left?: Dimension;
+  zIndex?: number;
+  transform?: Transform;
+  transformOrigin?: string;
+
+  gridColumn?: GridLine;
+  gridRow?: GridLine;
+}
+
+/** Style for inline text spans. Unset properties inherit from the text element. */
+export interface SpanStyle {
+  fontFamily?: string;
+  fontSize?: number;
+  fontWeight?: FontWeight;
+  fontStyle?: FontStyle;
+  fontStretch?: FontStretch;
+  color?: Color;
 }
 
 /** Style for Image elements. */
@@ -116,15 +390,36 @@ export interface ImageStyle {
   minHeight?: Dimension;
   maxHeight?: Dimension;
 
-  margin?: Spacing;
+  margin?: Margin;
 
   objectFit?: ObjectFit;
+  /** Image alignment: keywords, percentages or pixels (e.g. "left top", "25% 75%"). */
+  objectPosition?: string;
 
   borderRadius?: BorderRadius;
   opacity?: number;
+  boxShadow?: BoxShadow;
 
   flexGrow?: number;
   flexShrink?: number;
+  /** Initial main size: a Dimension or "auto" */
+  flexBasis?: Dimension;
+  /** Overrides the parent's alignItems */
+  alignSelf?: AlignItems;
+  /** Layout and paint position among flex/grid siblings (default 0) */
+  order?: number;
+
+  position?: Position;
+  top?: Dimension;
+  right?: Dimension;
+  bottom?: Dimension;
+  left?: Dimension;
+  zIndex?: number;
+  transform?: Transform;
+  transformOrigin?: string;
+
+  gridColumn?: GridLine;
+  gridRow?: GridLine;
 }
 
 // ============================================================================
@@ -145,13 +440,25 @@ export interface FlexElement {
   children?: Element[];
 }
 
-/** Text leaf element. */
-export interface TextElement {
-  type: "text";
+/** Grid container element. */
+export interface GridElement {
+  type: "grid";
+  style?: GridStyle;
+  children?: Element[];
+}
+
+/** An inline run of text with its own style. */
+export interface TextSpan {
   content: string;
-  style?: TextStyle;
+  style?: SpanStyle;
 }
 
+/** Text leaf element: plain `content` or styled `spans`, exactly one of them. */
+export type TextElement = {
+  type: "text";
+  style?: TextStyle;
+} & ({ content: string; spans?: never } | { spans: TextSpan[]; content?: never });
+
 /** Image element with intrinsic dimensions. */
 export interface ImageElement {
   type: "image";
@@ -162,7 +469,12 @@ export interface ImageElement {
 }
 
 /** Any HTVG element. */
-export type Element = BoxElement | FlexElement | TextElement | ImageElement;
+export type Element =
+  | BoxElement
+  | FlexElement
+  | GridElement
+  | TextElement
+  | ImageElement;
 
 // ============================================================================
 // Document & compilation types
@@ -176,6 +488,10 @@ export interface FontSource {
   url?: string;
   /** Font weight (default: 400). */
   weight?: number;
+  /** Font style (default: "normal"). */
+  style?: FontStyle;
+  /** Font stretch (default: "normal"). */
+  stretch?: FontStretch;
   /** Base64-encoded font data (TTF/OTF/WOFF2) — used for text layout. */
   data?: string;
 }
@@ -186,12 +502,18 @@ export interface CompileOptions {
   width: number;
   /** Output height in pixels (auto-computed if omitted). */
   height?: number;
-  /** Default font size (default: 16). */
+  /** Root font size: the size of rem lengths and the fontSize text inherits (default: 16). */
   fontSize?: number;
-  /** Font family applied to text elements without an explicit fontFamily. */
+  /** Root font family, inherited by text without a fontFamily. */
   fontFamily?: string;
+  /** Font families tried, in order, for characters a text's own fonts have no glyphs for (e.g. CJK, emoji). */
+  fontFallbacks?: string[];
   /** Fonts to register. */
   fonts?: FontSource[];
+  /** Embed fonts with `data` into the SVG as subsetted data-URI @font-face. */
+  embedFonts?: boolean;
+  /** Position every character of `<text>` output at its shaped glyph position, so viewers don't re-shape lines. */
+  glyphPositions?: boolean;
 }
 
 /** Self-contained HTVG document. */
@@ -210,6 +532,17 @@ export interface CompileResult {
   width: number;
   /** Computed height. */
   height: number;
+  /** Text runs with the font each resolved to. */
+  fontRuns: FontRun[];
   /** Any warnings during compilation. */
   warnings: string[];
 }
+
+/** A run of text laid out in a single font. */
+export interface FontRun {
+  text: string;
+  /** Family name of the font the run resolved to. */
+  fontFamily: string;
+  /** Whether the font has no glyph for some characters, which then render as boxes. */
+  missingGlyphs: boolean;
+}